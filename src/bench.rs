@@ -0,0 +1,256 @@
+//! Benchmark of search strategies
+//!
+//! `run` plays many seeded single player games with a `Strategy` driving the `Board` directly,
+//! and summarizes how many turns it took to find every treasure. Comparing the strategies helps
+//! tuning the difficulty of a board, and a change in their results points at a regression in
+//! the distances or the hints.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::board::{Board, BoardConfig};
+use crate::bot::{step_towards, Bot};
+use crate::utils::Point;
+
+/// Games still running after this many turns per square of the board are given up
+const MAX_TURNS_PER_SQUARE: u32 = 50;
+
+/// A way of playing a single player game, one turn at a time
+pub trait Strategy {
+    fn name(&self) -> &'static str;
+
+    /// Forgets the previous game, `seed` lets a random strategy replay the same game
+    fn start(&mut self, board: &Board, seed: u64);
+
+    /// Plays one move or one search on `board`
+    fn play(&mut self, board: &mut Board);
+}
+
+/// Searches its square when it was never searched, otherwise moves to a random square in range
+#[derive(Debug)]
+pub struct RandomWalk {
+    rng: StdRng,
+}
+
+impl RandomWalk {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+}
+
+impl Default for RandomWalk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Strategy for RandomWalk {
+    fn name(&self) -> &'static str {
+        "random walk"
+    }
+
+    fn start(&mut self, _board: &Board, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    fn play(&mut self, board: &mut Board) {
+        let position = board.player().position;
+        if !is_searched(board, position) {
+            board.search();
+            return;
+        }
+        let config = *board.config();
+        let range = |p: u8, size: u8| {
+            p.saturating_sub(config.max_dist)..=p.saturating_add(config.max_dist).min(size - 1)
+        };
+        loop {
+            let x = self.rng.gen_range(range(position.x, config.width));
+            let y = self.rng.gen_range(range(position.y, config.height));
+            if (x, y) != position.as_tuple() && board.move_to(x, y).is_ok() {
+                return;
+            }
+        }
+    }
+}
+
+/// Searches every square row after row, ignoring the replies and walking around the obstacles
+#[derive(Debug, Default)]
+pub struct GridSweep;
+
+impl Strategy for GridSweep {
+    fn name(&self) -> &'static str {
+        "grid sweep"
+    }
+
+    fn start(&mut self, _board: &Board, _seed: u64) {}
+
+    fn play(&mut self, board: &mut Board) {
+        let position = board.player().position;
+        if !is_searched(board, position) {
+            board.search();
+            return;
+        }
+        // rows alternate direction, so that the next square is always close
+        let config = *board.config();
+        let next = (0..config.height)
+            .flat_map(|y| {
+                (0..config.width).map(move |x| match y % 2 {
+                    0 => Point { x, y },
+                    _ => Point {
+                        x: config.width - 1 - x,
+                        y,
+                    },
+                })
+            })
+            .find(|square| board.is_passable(square.x, square.y) && !is_searched(board, *square))
+            .expect("every treasure is found once every square is searched");
+        let (x, y) = step_towards(board, position, next).as_tuple();
+        board.move_to(x, y).expect("the step is within range");
+    }
+}
+
+/// The computer player: rules out squares with the distance replies and walks to the closest
+/// square left
+#[derive(Debug, Default)]
+pub struct Trilateration {
+    bot: Option<Bot>,
+}
+
+impl Strategy for Trilateration {
+    fn name(&self) -> &'static str {
+        "trilateration"
+    }
+
+    fn start(&mut self, board: &Board, _seed: u64) {
+        self.bot = Some(Bot::new(board, 0));
+    }
+
+    fn play(&mut self, board: &mut Board) {
+        self.bot.as_mut().expect("the game was started").play(board);
+    }
+}
+
+fn is_searched(board: &Board, square: Point) -> bool {
+    board.board[square.x as usize][square.y as usize] == Board::SEARCHED_CHAR
+}
+
+/// Turns taken to find every treasure over a series of games
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub games: u32,
+    /// Games given up after `MAX_TURNS_PER_SQUARE` turns per square, left out of the figures
+    pub unfinished: u32,
+    pub mean: f64,
+    pub median: u32,
+    /// 95% of the games were won in this many turns or less
+    pub p95: u32,
+}
+
+impl Stats {
+    /// Summarizes the turns of the finished games, out of `games` played
+    pub fn new(mut turns: Vec<u32>, games: u32) -> Self {
+        turns.sort_unstable();
+        // nearest rank percentile
+        let percentile = |p: usize| match turns.len() {
+            0 => 0,
+            n => turns[(p * n).div_ceil(100).max(1) - 1],
+        };
+        Stats {
+            games,
+            unfinished: games - turns.len() as u32,
+            mean: match turns.len() {
+                0 => 0.0,
+                n => turns.iter().map(|&t| t as f64).sum::<f64>() / n as f64,
+            },
+            median: percentile(50),
+            p95: percentile(95),
+        }
+    }
+}
+
+/// Plays `games` single player games on boards of `config` with `strategy`, without a stamina
+/// limit
+///
+/// Game `i` is played on the board of seed `seed + i`, so that the strategies can be compared
+/// on the same games.
+pub fn run(strategy: &mut dyn Strategy, config: BoardConfig, games: u32, seed: u64) -> Stats {
+    let config = config.with_players(1).with_stamina(None);
+    let max_turns = MAX_TURNS_PER_SQUARE * config.width as u32 * config.height as u32;
+    let mut turns = Vec::new();
+    for game in 0..games as u64 {
+        let mut board = Board::from_seed(config, seed.wrapping_add(game));
+        strategy.start(&board, seed.wrapping_add(game));
+        while !board.is_treasure_found() && (board.turns().len() as u32) < max_turns {
+            strategy.play(&mut board);
+        }
+        if board.is_treasure_found() {
+            turns.push(board.turns().len() as u32);
+        }
+    }
+    Stats::new(turns, games)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::Metric;
+    use rstest::rstest;
+
+    #[rstest(
+        turns,
+        expected,
+        case(vec![4], (4.0, 4, 4)),
+        case(vec![3, 1, 2], (2.0, 2, 3)),
+        case((1..=100).collect(), (50.5, 50, 95)),
+        case(vec![], (0.0, 0, 0)),
+        ::trace
+    )]
+    fn stats_tests(turns: Vec<u32>, expected: (f64, u32, u32)) {
+        let stats = Stats::new(turns, 100);
+        assert_eq!((stats.mean, stats.median, stats.p95), expected);
+    }
+
+    #[rstest(
+        metric,
+        case(Metric::Chebyshev),
+        case(Metric::Manhattan),
+        case(Metric::Euclidean),
+        ::trace
+    )]
+    fn strategies_finish(metric: Metric) {
+        for obstacles in [0, 25] {
+            let config = BoardConfig::new(6, 5, 2)
+                .with_metric(metric)
+                .with_obstacles(obstacles);
+            let strategies: [&mut dyn Strategy; 3] = [
+                &mut RandomWalk::new(),
+                &mut GridSweep,
+                &mut Trilateration::default(),
+            ];
+            for strategy in strategies {
+                let stats = run(strategy, config, 20, 7);
+                assert_eq!(stats.unfinished, 0, "{}", strategy.name());
+            }
+        }
+    }
+
+    #[test]
+    fn trilateration_beats_sweep() {
+        let config = BoardConfig::new(15, 15, 4);
+        let sweep = run(&mut GridSweep, config, 50, 1);
+        let trilateration = run(&mut Trilateration::default(), config, 50, 1);
+        assert!(trilateration.mean * 3.0 < sweep.mean);
+        assert!(trilateration.p95 < sweep.median);
+    }
+
+    #[test]
+    fn runs_are_seeded() {
+        let config = BoardConfig::new(8, 8, 3);
+        assert_eq!(
+            run(&mut RandomWalk::new(), config, 10, 3),
+            run(&mut RandomWalk::new(), config, 10, 3)
+        );
+    }
+}
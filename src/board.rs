@@ -1,133 +1,826 @@
-#[path = "./player.rs"]
-pub mod player;
-
-#[path = "./utils.rs"]
-mod utils;
-
-use crate::utils::abs;
-use core::option::Option::Some;
-use p::Player;
-use player as p;
-use rand::prelude::ThreadRng;
-use rand::Rng;
+use crate::history::{Score, Turn};
+use crate::outcome::{
+    Axis, CoordError, Direction, Hint, HintMode, MoveError, SearchOutcome, UndoError,
+};
+use crate::player::Player;
+use crate::terrain;
+use crate::utils::{Metric, Point};
+use rand::distributions::Distribution;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use regex::Regex;
-use std::cmp::max;
-use std::io::{Result, Write};
-use termcolor::{BufferWriter, Color, ColorChoice, ColorSpec, WriteColor};
-use u::Point;
-use utils as u;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use strum_macros::{Display, EnumString};
+
+/// Dimensions, movement range, distance metric, number of treasures and players of a `Board`
+///
+/// The default is the classic 15x15 board with a single player, a single treasure and a 4 blocs
+/// movement limit, where moving diagonally counts as a single bloc.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoardConfig {
+    pub width: u8,
+    pub height: u8,
+    pub max_dist: u8,
+    /// Missing from save files written before metrics could be chosen
+    #[serde(default)]
+    pub metric: Metric,
+    /// Missing from save files written before hints could be chosen
+    #[serde(default)]
+    pub hints: HintMode,
+    /// Number of treasures hidden on the board, at most one per square
+    #[serde(default = "BoardConfig::default_count")]
+    pub treasures: u8,
+    /// Number of players taking turns on the board
+    #[serde(default = "BoardConfig::default_count")]
+    pub players: u8,
+    /// Whether moves and searches can be taken back, such games are not scored. Missing from
+    /// save files written before version 7
+    #[serde(default)]
+    pub practice: bool,
+    /// Radius of the fog of war: the players only see the squares this many blocs away and the
+    /// squares already searched. None shows the whole board. Missing from save files written
+    /// before version 8
+    #[serde(default)]
+    pub visibility: Option<u8>,
+    /// Percentage of the squares turned into walls and water. Missing from save files written
+    /// before version 9
+    #[serde(default)]
+    pub obstacles: u8,
+    /// Stamina of each player, moves cost one per bloc and searches `Board::SEARCH_COST`. None
+    /// is unlimited. Missing from save files written before version 11
+    #[serde(default)]
+    pub stamina: Option<u16>,
+    /// Number of turns between two drifts of the hidden treasures, None keeps them in place.
+    /// Missing from save files written before version 12
+    #[serde(default)]
+    pub drift: Option<u8>,
+}
+
+impl BoardConfig {
+    pub fn new(width: u8, height: u8, max_dist: u8) -> Self {
+        Self {
+            width,
+            height,
+            max_dist,
+            metric: Metric::default(),
+            hints: HintMode::default(),
+            treasures: Self::default_count(),
+            players: Self::default_count(),
+            practice: false,
+            visibility: None,
+            obstacles: 0,
+            stamina: None,
+            drift: None,
+        }
+    }
+
+    /// A single treasure and a single player
+    fn default_count() -> u8 {
+        1
+    }
+
+    pub fn with_metric(self, metric: Metric) -> Self {
+        Self { metric, ..self }
+    }
+
+    pub fn with_hints(self, hints: HintMode) -> Self {
+        Self { hints, ..self }
+    }
+
+    pub fn with_treasures(self, treasures: u8) -> Self {
+        Self { treasures, ..self }
+    }
+
+    pub fn with_players(self, players: u8) -> Self {
+        Self { players, ..self }
+    }
+
+    pub fn with_practice(self, practice: bool) -> Self {
+        Self { practice, ..self }
+    }
+
+    pub fn with_visibility(self, visibility: Option<u8>) -> Self {
+        Self { visibility, ..self }
+    }
+
+    pub fn with_obstacles(self, obstacles: u8) -> Self {
+        Self { obstacles, ..self }
+    }
+
+    pub fn with_stamina(self, stamina: Option<u16>) -> Self {
+        Self { stamina, ..self }
+    }
+
+    pub fn with_drift(self, drift: Option<u8>) -> Self {
+        Self { drift, ..self }
+    }
+
+    /// The board size, move range, metric, hints, stamina and treasures of `difficulty`
+    ///
+    /// The players, practice mode, fog and obstacles are kept.
+    pub fn with_difficulty(self, difficulty: Difficulty) -> Self {
+        let (size, max_dist, metric, hints, stamina, treasures) = match difficulty {
+            Difficulty::Easy => (10, 5, Metric::Chebyshev, HintMode::Compass, None, 1),
+            Difficulty::Normal => (15, 4, Metric::Chebyshev, HintMode::None, None, 1),
+            Difficulty::Hard => (20, 3, Metric::Manhattan, HintMode::HotCold, Some(150), 2),
+            Difficulty::Insane => (32, 3, Metric::Euclidean, HintMode::None, Some(200), 3),
+        };
+        Self {
+            width: size,
+            height: size,
+            max_dist,
+            metric,
+            hints,
+            stamina,
+            treasures,
+            ..self
+        }
+    }
+}
+
+/// Presets of the board settings, from a small board with compass hints to large boards
+/// with several treasures and a stamina budget
+#[derive(Debug, Clone, Copy, PartialEq, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    /// The classic board of `BoardConfig::default`
+    Normal,
+    Hard,
+    Insane,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Insane,
+    ];
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        Self::new(15, 15, 4)
+    }
+}
+
+/// A hidden treasure and whether a search already collected it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Treasure {
+    pub position: Point,
+    pub found: bool,
+}
+
+/// The treasure at index `treasure` of `Board::treasures` drifting from `from` to `to`, once
+/// `turn` turns were played
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Drift {
+    pub turn: usize,
+    pub treasure: usize,
+    pub from: Point,
+    pub to: Point,
+}
 
 #[derive(Debug)]
 pub struct Board {
     pub board: Vec<Vec<char>>,
-    pub player: Player,
-    treasure: Point,
-    pub(crate) treasure_found: bool,
+    /// Players in turn order, there is always at least one
+    pub players: Vec<Player>,
+    /// Index of the player whose turn it is
+    pub(crate) current: usize,
+    pub(crate) config: BoardConfig,
+    pub(crate) treasures: Vec<Treasure>,
+    pub(crate) turns: Vec<Turn>,
+    /// Turns taken back by `undo`, the last one is the next to `redo`. They are not saved
+    pub(crate) undone: Vec<Turn>,
+    /// Seed of the directions the treasures drift in, see `BoardConfig::drift`
+    pub(crate) drift_seed: u64,
+    /// Every drift of the treasures so far, oldest first, so that `undo` can take them back
+    pub(crate) drifts: Vec<Drift>,
 }
 
 impl Board {
-    const BOARD_COLOR: Color = Color::White;
-    pub(crate) const BOARD_WIDTH: u8 = 15;
-    pub(crate) const BOARD_HEIGHT: u8 = 15;
-    pub(crate) const MAX_DIST: u8 = 4;
-
-    const EMPTY_CHAR: char = '.';
-    pub(crate) const PLAYER_CHAR: char = '@';
-    pub(crate) const SEARCHED_CHAR: char = 'X';
-
-    pub fn new() -> Self {
-        let size = (Self::BOARD_WIDTH, Self::BOARD_HEIGHT);
-        let mut rng: ThreadRng = rand::thread_rng();
-        let rand_point: Point = rng.gen();
+    pub const EMPTY_CHAR: char = '.';
+    pub const PLAYER_CHAR: char = '@';
+    pub const SEARCHED_CHAR: char = 'X';
+    pub const TREASURE_CHAR: char = '$';
+    /// Squares hidden by the fog of war
+    pub const FOG_CHAR: char = ' ';
+    /// Obstacles no player can walk through
+    pub const WALL_CHAR: char = '#';
+    pub const WATER_CHAR: char = '~';
+    /// Squares walked through by the last move
+    pub const ROUTE_CHAR: char = '+';
+    /// Stamina spent by a search, whatever it finds
+    pub const SEARCH_COST: u16 = 3;
+
+    pub fn new(config: BoardConfig) -> Self {
+        Self::with_rng(config, &mut rand::thread_rng())
+    }
+
+    /// Creates a board whose treasures and player spawns are entirely determined by `seed`
+    ///
+    /// Two boards created with the same config and seed are identical, which lets a game be replayed.
+    pub fn from_seed(config: BoardConfig, seed: u64) -> Self {
+        Self::with_rng(config, &mut StdRng::seed_from_u64(seed))
+    }
+
+    /// Creates a board drawing the treasures and then the spawn of each player from `rng`
+    ///
+    /// Treasures are hidden on distinct squares, `config.treasures` is capped to the number of
+    /// squares of the board. Players may spawn on the same square, there is at least one. The
+    /// obstacles are placed around the treasures and the players, and the seed of the drift of
+    /// the treasures is drawn last.
+    pub fn with_rng<R: Rng + ?Sized>(config: BoardConfig, rng: &mut R) -> Self {
+        let squares = config.width as usize * config.height as usize;
+        let mut treasures: Vec<Treasure> = Vec::new();
+        while treasures.len() < squares.min(config.treasures as usize) {
+            let rand_point: Point = config.sample(rng);
+            if treasures.iter().all(|t| t.position != rand_point) {
+                treasures.push(Treasure {
+                    position: rand_point,
+                    found: false,
+                });
+            }
+        }
+        let players: Vec<Player> = (0..config.players.max(1))
+            .map(|_| Player::with_rng(&config, rng))
+            .collect();
+        // the grid is indexed as board[x][y]
+        let mut board = vec![vec![Self::EMPTY_CHAR; config.height as usize]; config.width as usize];
+        if config.obstacles > 0 {
+            let keep: Vec<Point> = treasures
+                .iter()
+                .map(|t| t.position)
+                .chain(players.iter().map(|p| p.position))
+                .collect();
+            terrain::place_obstacles(&mut board, config.metric, config.obstacles, &keep, rng);
+        }
+        let drift_seed = match config.drift {
+            Some(_) => rng.gen(),
+            None => 0,
+        };
         Self {
-            board: vec![vec![Self::EMPTY_CHAR; size.0 as usize]; size.1 as usize],
-            player: Player::new(),
-            treasure: rand_point,
-            treasure_found: false,
+            board,
+            players,
+            current: 0,
+            config,
+            treasures,
+            turns: Vec::new(),
+            undone: Vec::new(),
+            drift_seed,
+            drifts: Vec::new(),
         }
     }
 
-    /// Search for the treasure on the player space
+    /// The dimensions and movement range this board was created with
+    pub fn config(&self) -> &BoardConfig {
+        &self.config
+    }
+
+    /// true once the player has searched every treasure square
+    pub fn is_treasure_found(&self) -> bool {
+        self.treasures.iter().all(|t| t.found)
+    }
+
+    /// true once every player ran out of stamina with treasures still hidden
+    pub fn is_lost(&self) -> bool {
+        !self.is_treasure_found() && (0..self.players.len()).all(|p| self.is_exhausted(p))
+    }
+
+    /// true once the game is won or lost, no more turns can be played
+    pub fn is_over(&self) -> bool {
+        self.is_treasure_found() || self.is_lost()
+    }
+
+    /// Stamina `player` has not spent yet, None when it is unlimited
+    pub fn stamina_left(&self, player: usize) -> Option<u16> {
+        let spent: u32 = self
+            .turns
+            .iter()
+            .filter(|turn| turn.player() == player)
+            .map(|turn| match turn {
+                Turn::Move { distance, .. } => *distance as u32,
+                Turn::Search { .. } => Self::SEARCH_COST as u32,
+            })
+            .sum();
+        self.config
+            .stamina
+            .map(|stamina| (stamina as u32).saturating_sub(spent) as u16)
+    }
+
+    /// Whether `player` can no longer afford a search, and so is out of the game
+    pub fn is_exhausted(&self, player: usize) -> bool {
+        self.stamina_left(player)
+            .is_some_and(|left| left < Self::SEARCH_COST)
+    }
+
+    /// Every treasure of the board, found or not
+    pub fn treasures(&self) -> &[Treasure] {
+        &self.treasures
+    }
+
+    /// Number of treasures still hidden
+    pub fn treasures_left(&self) -> usize {
+        self.treasures.iter().filter(|t| !t.found).count()
+    }
+
+    /// The hidden treasure closest to the player, None once they are all found
+    fn nearest_treasure(&self) -> Option<Point> {
+        self.treasures
+            .iter()
+            .filter(|t| !t.found)
+            .map(|t| t.position)
+            .min_by_key(|p| self.get_distance_to(p.x, p.y))
+    }
+
+    /// Whether a player can stand on the square (x,y), which is neither a wall nor water
+    pub fn is_passable(&self, x: u8, y: u8) -> bool {
+        terrain::is_passable(self.board[x as usize][y as usize])
+    }
+
+    /// Steps needed to walk from `from` to every square, around the obstacles
+    ///
+    /// See `terrain::walking_distances`, squares more than `limit` steps away are None.
+    pub(crate) fn walking_distances(&self, from: Point, limit: u16) -> Vec<Vec<Option<u16>>> {
+        terrain::walking_distances(&self.board, self.config.metric, from, limit)
+    }
+
+    /// The squares matching `is_goal` the fewest steps away from `from`, around the obstacles
+    pub(crate) fn closest_on_foot<F: Fn(&Point) -> bool>(
+        &self,
+        from: Point,
+        is_goal: F,
+    ) -> Vec<Point> {
+        terrain::closest_on_foot(&self.board, self.config.metric, from, is_goal)
+    }
+
+    /// Whether `player` can see the square (x,y) through the fog of war
     ///
-    /// If the treasure is hidden there, we congratulate the player and .
-    /// If not, display a message indicating the distance to the treasure in blocs.
-    /// Save and display the searched position on the board.
-    pub(crate) fn search(&mut self) {
-        let x: usize = self.player.position.x as usize;
-        let y: usize = self.player.position.y as usize;
+    /// Searched squares stay visible, and the fog lifts once the game is over.
+    pub fn is_visible(&self, player: usize, x: u8, y: u8) -> bool {
+        let radius = match self.config.visibility {
+            Some(radius) => radius,
+            None => return true,
+        };
+        let position = self.players[player].position;
+        self.is_over()
+            || self.board[x as usize][y as usize] == Self::SEARCHED_CHAR
+            || position.distance_to(&Point { x, y }, self.config.metric) <= radius as u16
+    }
+
+    /// Index in `players` of the player whose turn it is
+    pub fn current_player(&self) -> usize {
+        self.current
+    }
+
+    /// The player whose turn it is
+    pub fn player(&self) -> &Player {
+        &self.players[self.current]
+    }
+
+    pub fn player_mut(&mut self) -> &mut Player {
+        &mut self.players[self.current]
+    }
+
+    /// Every move and search made so far by all the players, oldest first
+    pub fn turns(&self) -> &[Turn] {
+        &self.turns
+    }
+
+    /// Score of a player in the game so far
+    pub fn score(&self, player: usize) -> Score {
+        let start = self.players[player].start;
+        // measured from where the treasures were hidden, before they drifted
+        let farthest = self
+            .treasures
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                let hidden = self
+                    .drifts
+                    .iter()
+                    .find(|drift| drift.treasure == i)
+                    .map_or(t.position, |drift| drift.from);
+                start.distance_to(&hidden, self.config.metric)
+            })
+            .max()
+            .unwrap_or(0);
+        let turns: Vec<Turn> = self
+            .turns
+            .iter()
+            .filter(|turn| turn.player() == player)
+            .cloned()
+            .collect();
+        Score::new(
+            &turns,
+            farthest,
+            self.config.max_dist,
+            self.treasures.len() as u32,
+        )
+    }
+
+    /// The player who collected the most treasures, once they are all found
+    ///
+    /// On a tie, the player who reached that number of treasures first wins.
+    pub fn winner(&self) -> Option<usize> {
+        if !self.is_treasure_found() {
+            return None;
+        }
+        let mut found = vec![0; self.players.len()];
+        let mut winner = None;
+        let mut best = 0;
+        for turn in &self.turns {
+            if let Turn::Search {
+                outcome: SearchOutcome::Found,
+                player,
+                ..
+            } = turn
+            {
+                found[*player] += 1;
+                if found[*player] > best {
+                    best = found[*player];
+                    winner = Some(*player);
+                }
+            }
+        }
+        // the turn log of old save files may be missing
+        winner.or(Some(self.current))
+    }
+
+    /// Number of times the hidden treasures drifted so far, on boards where they drift
+    pub fn drift_count(&self) -> usize {
+        match self.config.drift {
+            Some(every) if every > 0 => self.turns.len() / every as usize,
+            _ => 0,
+        }
+    }
+
+    /// Carries every hidden treasure a single step in a random direction, every
+    /// `BoardConfig::drift` turns
+    ///
+    /// The directions only depend on the drift seed and the number of turns played, so that
+    /// `redo` replays the same drift. A treasure never drifts onto an obstacle, a searched square
+    /// or another treasure, it stays put when it has nowhere to go.
+    fn drift(&mut self) {
+        let turn = self.turns.len();
+        match self.config.drift {
+            Some(every) if every > 0 && turn.is_multiple_of(every as usize) => {}
+            _ => return,
+        }
+        let mut rng = StdRng::seed_from_u64(self.drift_seed.wrapping_add(turn as u64));
+        for treasure in 0..self.treasures.len() {
+            if self.treasures[treasure].found {
+                continue;
+            }
+            let from = self.treasures[treasure].position;
+            let free: Vec<Point> = terrain::steps(&self.board, self.config.metric, from)
+                .into_iter()
+                .filter(|p| self.board[p.x as usize][p.y as usize] == Self::EMPTY_CHAR)
+                .filter(|p| self.treasures.iter().all(|t| t.position != *p))
+                .collect();
+            if let Some(&to) = free.choose(&mut rng) {
+                self.treasures[treasure].position = to;
+                self.drifts.push(Drift {
+                    turn,
+                    treasure,
+                    from,
+                    to,
+                });
+            }
+        }
+    }
+
+    /// Lets the treasures drift, then hands over to the next player who has stamina left,
+    /// unless the game is over
+    fn end_turn(&mut self) {
+        self.drift();
+        if self.is_over() {
+            return;
+        }
+        loop {
+            self.current = (self.current + 1) % self.players.len();
+            if !self.is_exhausted(self.current) {
+                break;
+            }
+        }
+    }
+
+    /// Search for a treasure on the space of the current player, then hand over to the next one
+    ///
+    /// Save the searched position on the board.
+    /// Returns `SearchOutcome::Found` if a treasure not yet found is hidden there, otherwise
+    /// the distance to the nearest hidden treasure in blocs and a hint if the `HintMode` gives one.
+    pub fn search(&mut self) -> SearchOutcome {
+        let x: usize = self.player().position.x as usize;
+        let y: usize = self.player().position.y as usize;
 
         // save position searched, also used for the display
         self.board[x][y] = Self::SEARCHED_CHAR;
 
-        // we found the treasure
-        if self.player.position.as_tuple() == self.treasure.as_tuple() {
-            println!("Congratulation you found the treasure!");
-            self.treasure_found = true;
-        } else {
-            let distance = self.get_distance_to(self.treasure.x, self.treasure.y);
-            let buffer_writer = BufferWriter::stdout(ColorChoice::Always);
-            let mut buffer = buffer_writer.buffer();
-            // The Treasure is X blocs away. (with highlight on X)
-            buffer
-                .set_color(ColorSpec::new().set_fg(Some(Color::White)))
-                .map_err(|err| println!("{:?}", err))
-                .ok();
-            write!(&mut buffer, "The treasure is ")
-                .map_err(|err| println!("{:?}", err))
-                .ok();
-            buffer
-                .set_color(ColorSpec::new().set_fg(Some(Color::Rgb(0, 102, 255))))
-                .map_err(|err| println!("{:?}", err))
-                .ok();
-            write!(&mut buffer, "{}", distance)
-                .map_err(|err| println!("{:?}", err))
-                .ok();
-            buffer
-                .set_color(ColorSpec::new().set_fg(Some(Color::White)))
-                .map_err(|err| println!("{:?}", err))
-                .ok();
-            writeln!(&mut buffer, " blocs away.")
-                .map_err(|err| println!("{:?}", err))
-                .ok();
-            buffer_writer
-                .print(&buffer)
-                .map_err(|err| println!("{:?}", err))
-                .ok();
-        }
-    }
-
-    pub(crate) fn move_to(&mut self, x: u8, y: u8) {
-        let distance = self.get_distance_to(x, y);
-        if distance > Self::MAX_DIST {
-            println!("You cannot move more than 4 blocs in a turn!");
-            return;
-        } else {
-            self.player.position.set((x, y));
+        let position = self.player().position;
+        let outcome = match self
+            .treasures
+            .iter_mut()
+            .find(|t| !t.found && t.position == position)
+        {
+            // we found a treasure
+            Some(treasure) => {
+                treasure.found = true;
+                SearchOutcome::Found
+            }
+            None => match self.nearest_treasure() {
+                Some(nearest) => {
+                    let distance = self.get_distance_to(nearest.x, nearest.y);
+                    SearchOutcome::Distance(distance, self.hint(nearest, distance))
+                }
+                // nothing left to find, the game is over
+                None => SearchOutcome::Distance(0, None),
+            },
+        };
+        self.turns.push(Turn::Search {
+            at: position,
+            outcome,
+            player: self.current,
+        });
+        self.undone.clear();
+        self.end_turn();
+        outcome
+    }
+
+    /// Hint given by a failed search from the current player's position, `nearest` being the
+    /// closest hidden treasure
+    ///
+    /// Must be called before the search is added to the turn log, the hot/cold comparison is
+    /// made against the previous search of the same player. Their first search has nothing to
+    /// compare to.
+    fn hint(&self, nearest: Point, distance: u16) -> Option<Hint> {
+        match self.config.hints {
+            HintMode::None => None,
+            HintMode::Compass => {
+                Direction::between(&self.player().position, &nearest).map(Hint::Direction)
+            }
+            HintMode::HotCold => self.turns.iter().rev().find_map(|turn| match turn {
+                Turn::Search {
+                    outcome: SearchOutcome::Distance(previous, _),
+                    player,
+                    ..
+                } if *player == self.current => Some(match distance.cmp(previous) {
+                    Ordering::Less => Hint::Warmer,
+                    Ordering::Greater => Hint::Colder,
+                    Ordering::Equal => Hint::Same,
+                }),
+                _ => None,
+            }),
         }
     }
 
-    /// gives the distance from the player
-    /// return u8: Distance between player and (x,y)
-    pub fn get_distance_to(&self, x: u8, y: u8) -> u8 {
-        max(
-            abs(x as i32 - self.player.position.x as i32) as u8,
-            abs(y as i32 - self.player.position.y as i32) as u8,
+    /// Moves the current player to (x,y) by the shortest walk, see `route_to`, then hands over
+    /// to the next player
+    pub fn move_to(&mut self, x: u8, y: u8) -> Result<(), MoveError> {
+        let route = self.route_to(x, y)?;
+        self.move_along(&route)
+    }
+
+    /// The shortest walk of the current player to (x,y), around the walls and water, as the
+    /// squares stepped on
+    ///
+    /// (x,y) must be on the board and within `max_dist` blocs, in a straight line and on foot.
+    pub fn route_to(&self, x: u8, y: u8) -> Result<Vec<Point>, MoveError> {
+        self.is_within_bounds(x, y)?;
+        let max = self.config.max_dist;
+        if self.get_distance_to(x, y) > max as u16 {
+            return Err(MoveError::TooFar { max });
+        }
+        if !self.is_passable(x, y) {
+            return Err(MoveError::Blocked);
+        }
+        terrain::route(
+            &self.board,
+            self.config.metric,
+            self.player().position,
+            Point { x, y },
+            max as u16,
         )
+        .ok_or(MoveError::NoPath { max })
+    }
+
+    /// Walks the current player along `route`, one step per square, then hands over to the next
+    /// player
+    ///
+    /// Every square must be a single step away from the one before it, see `terrain::is_step`.
+    /// The distance of the move is the longest of the straight line and the number of steps,
+    /// neither may be over `max_dist` blocs nor the stamina left. The route must leave the square
+    /// of the player.
+    pub fn move_along(&mut self, route: &[Point]) -> Result<(), MoveError> {
+        let max = self.config.max_dist;
+        if route.is_empty() {
+            return Err(MoveError::Stay);
+        }
+        if route.len() > max as usize {
+            return Err(MoveError::TooFar { max });
+        }
+        let from = self.player().position;
+        let mut previous = from;
+        for &square in route {
+            self.is_within_bounds(square.x, square.y)?;
+            if !self.is_passable(square.x, square.y) {
+                return Err(MoveError::Blocked);
+            }
+            if !terrain::is_step(&self.board, self.config.metric, previous, square) {
+                return Err(MoveError::NotAStep { at: square });
+            }
+            previous = square;
+        }
+        let to = previous;
+        let distance = self.get_distance_to(to.x, to.y).max(route.len() as u16);
+        if distance > max as u16 {
+            return Err(MoveError::TooFar { max });
+        }
+        if let Some(left) = self.stamina_left(self.current) {
+            if distance > left {
+                return Err(MoveError::Exhausted { left });
+            }
+        }
+        self.player_mut().position = to;
+        self.turns.push(Turn::Move {
+            from,
+            to,
+            distance: distance as u8,
+            player: self.current,
+            route: route.to_vec(),
+        });
+        self.undone.clear();
+        self.end_turn();
+        Ok(())
+    }
+
+    /// Reads the route of a move command
+    ///
+    /// The input is either a destination in the syntax of `validate_move_coordinates`, reached
+    /// by the shortest walk, the squares of the route one after the other, as in `(1,1) (2,1)`,
+    /// or compass steps, as in `N3 E1` for three steps north then one east.
+    pub fn parse_move(&self, input: &str) -> Result<Vec<Point>, MoveError> {
+        let input = input.trim();
+        if input.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return self.parse_steps(input);
+        }
+        let mut squares = Vec::new();
+        let mut rest = input;
+        while let Some(end) = rest.find(&[')', ']'][..]) {
+            squares.push(rest[..=end].trim_start_matches(|c: char| c == ',' || c.is_whitespace()));
+            rest = &rest[end + 1..];
+        }
+        if squares.len() <= 1 {
+            let (x, y) = self.validate_move_coordinates(input)?;
+            return self.route_to(x, y);
+        }
+        if !rest.trim().is_empty() {
+            squares.push(rest);
+        }
+        let mut route = Vec::new();
+        for square in squares {
+            let (x, y) = self.validate_move_coordinates(square)?;
+            route.push(Point { x, y });
+        }
+        // the route may start with the square of the player
+        if route.first() == Some(&self.player().position) {
+            route.remove(0);
+        }
+        Ok(route)
+    }
+
+    /// Route of compass steps such as `N3 E1`, a direction without a number is a single step
+    fn parse_steps(&self, input: &str) -> Result<Vec<Point>, MoveError> {
+        let mut route = Vec::new();
+        let (mut x, mut y) = (
+            self.player().position.x as i16,
+            self.player().position.y as i16,
+        );
+        let words = input.split(|c: char| c == ',' || c.is_whitespace());
+        for word in words.filter(|word| !word.is_empty()) {
+            let split = word
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(word.len());
+            let direction: Direction = word[..split]
+                .to_uppercase()
+                .parse()
+                .map_err(|_| MoveError::Step(word.to_string()))?;
+            let count = match &word[split..] {
+                "" => 1,
+                count => count
+                    .parse::<u8>()
+                    .map_err(|_| MoveError::Step(word.to_string()))?,
+            };
+            let (dx, dy) = direction.offset();
+            for _ in 0..count {
+                x += dx;
+                y += dy;
+                if x < 0 || y < 0 || x >= self.config.width as i16 || y >= self.config.height as i16
+                {
+                    return Err(MoveError::OffBoard);
+                }
+                route.push(Point {
+                    x: x as u8,
+                    y: y as u8,
+                });
+            }
+        }
+        Ok(route)
+    }
+
+    /// Takes back the last move or search of a practice game, the player who took it gets the
+    /// turn back
+    ///
+    /// Returns the turn taken back, `redo` plays it again until another turn is played.
+    pub fn undo(&mut self) -> Result<Turn, UndoError> {
+        if !self.config.practice {
+            return Err(UndoError::NotPractice);
+        }
+        // the treasures drift back first, the turn might have found one where it drifted to
+        while let Some(drift) = self.drifts.last().filter(|d| d.turn == self.turns.len()) {
+            self.treasures[drift.treasure].position = drift.from;
+            self.drifts.pop();
+        }
+        let turn = self.turns.pop().ok_or(UndoError::NothingToUndo)?;
+        match turn {
+            Turn::Move { from, player, .. } => self.players[player].position = from,
+            Turn::Search { at, outcome, .. } => {
+                if outcome == SearchOutcome::Found {
+                    if let Some(treasure) = self
+                        .treasures
+                        .iter_mut()
+                        .find(|t| t.found && t.position == at)
+                    {
+                        treasure.found = false;
+                    }
+                }
+                // the square stays marked if it was searched before
+                let searched_before = self
+                    .turns
+                    .iter()
+                    .any(|turn| matches!(turn, Turn::Search { at: square, .. } if *square == at));
+                if !searched_before {
+                    self.board[at.x as usize][at.y as usize] = Self::EMPTY_CHAR;
+                }
+            }
+        }
+        self.current = turn.player();
+        self.undone.push(turn.clone());
+        Ok(turn)
+    }
+
+    /// Plays again the last turn taken back by `undo`, and returns it
+    pub fn redo(&mut self) -> Result<Turn, UndoError> {
+        if !self.config.practice {
+            return Err(UndoError::NotPractice);
+        }
+        let turn = self.undone.pop().ok_or(UndoError::NothingToRedo)?;
+        // playing the turn forgets the turns taken back, the ones left are kept
+        let undone = std::mem::take(&mut self.undone);
+        self.current = turn.player();
+        match &turn {
+            // the route is missing from the moves of old save files
+            Turn::Move {
+                from, to, route, ..
+            } if route.is_empty() && from != to => self
+                .move_to(to.x, to.y)
+                .expect("a move taken back is valid again"),
+            Turn::Move { route, .. } => self
+                .move_along(route)
+                .expect("a move taken back is valid again"),
+            Turn::Search { .. } => {
+                self.search();
+            }
+        }
+        self.undone = undone;
+        Ok(self.turns.last().unwrap().clone())
+    }
+
+    /// gives the distance from the current player, measured with the metric of the board
+    /// return u16: Distance between player and (x,y)
+    pub fn get_distance_to(&self, x: u8, y: u8) -> u16 {
+        self.player()
+            .position
+            .distance_to(&Point { x, y }, self.config.metric)
     }
 
     /// Verifies that the string if of the format [number,number] or (number,number)
     /// number : a base 10 or base 16 (with 0x prefix) number
-    /// a coordinate cannot be outside of our map i.e.: x ϵ [0;BOARD_WIDTH), y ϵ [0;BOARD_HEIGHT)
+    /// a coordinate cannot be outside of our map i.e.: x ϵ [0;width), y ϵ [0;height)
     ///
-    /// A coordinate cannot be more than MAX_DIST away from the player
+    /// The distance to the player is not checked here, `move_to` takes care of it.
     ///
     /// This function goes through multiple checkpoints to validate a set of coordinates
     /// It also ignores any space in the given string
     ///
-    /// Return true On success.  false On failure to validate
-    pub fn validate_move_coordinates(&self, coords: &str) -> (bool, u8, u8) {
-        if coords == "" {
-            return (false, 0, 0);
+    /// Return the (x,y) coordinates on success, the first failed check otherwise
+    pub fn validate_move_coordinates(&self, coords: &str) -> Result<(u8, u8), CoordError> {
+        if coords.is_empty() {
+            return Err(CoordError::Empty);
         }
         const MAX_DIMENSIONS: usize = 2;
         // remove whitespace for ease of use
@@ -141,209 +834,66 @@ impl Board {
 
         let re = Regex::new(COORD_REGEX).unwrap();
         if !re.is_match(s.as_str()) {
-            println!("Incorrect format, please check your input!");
-            return (false, 0, 0);
+            return Err(CoordError::Format);
         }
 
         // check parenthesis match
-        match s.chars().next().unwrap() {
-            '[' => {
-                if s.chars().last().unwrap() != ']' {
-                    println!("If you use '[' for your coordinates, do not forget to end your input with ']'!\n");
-                    return (false, 0, 0);
-                }
-            }
-            '(' => {
-                if s.chars().last().unwrap() != ')' {
-                    println!("If you use '(' for your coordinates, do not forget to end your input with ')'!\n");
-                    return (false, 0, 0);
-                }
-            }
-            _ => {
-                println!("Incorrect first character, please check your input!");
-                return (false, 0, 0);
-            }
+        let open = s.chars().next().unwrap();
+        let close = match open {
+            '[' => ']',
+            '(' => ')',
+            _ => return Err(CoordError::Format),
+        };
+        if !s.ends_with(close) {
+            return Err(CoordError::MismatchedBracket { open, close });
         }
 
         // parenthesis are ok, we now want to extract the coordinates and check them.
         let mut split: Vec<&str> = s.split(&['(', ')', '[', ']', ','][..]).collect();
-        split.retain(|&i| i != "");
+        split.retain(|&i| !i.is_empty());
 
-        if split.len() > MAX_DIMENSIONS {
-            println!(
-                "Wrong number of coordinates: {} coordinates provided instead of {}.",
-                split.len(),
-                MAX_DIMENSIONS
-            );
-            return (false, 0, 0);
+        if split.len() != MAX_DIMENSIONS {
+            return Err(CoordError::Dimensions {
+                got: split.len(),
+                expected: MAX_DIMENSIONS,
+            });
         }
 
         // For each of the two coordinates we check the base and convert it if the range is valid
-        let mut coords_as_u8 = vec![u8::MAX, u8::MAX];
-        for i in 0..MAX_DIMENSIONS {
-            if split.get(i) == None {
-                return (false, 0, 0);
-            }
+        let mut coords_as_u8 = [u8::MAX, u8::MAX];
+        for (coord, number) in coords_as_u8.iter_mut().zip(&split) {
             // dealing with hex
-            if split.get(i).unwrap().len() > 2 && split.get(i).unwrap()[..2].contains("0x") {
-                let without_prefix = split.get(i).unwrap().trim_start_matches("0x");
-                coords_as_u8[i] = match u8::from_str_radix(without_prefix, 16) {
-                    Ok(res) => res,
-                    Err(_) => {
-                        println!("The number {} can not be converted to a coordinate", {
-                            split.get(i).unwrap()
-                        });
-                        return (false, 0, 0);
-                    }
-                };
+            let parsed = if number.len() > 2 && number[..2].contains("0x") {
+                u8::from_str_radix(number.trim_start_matches("0x"), 16)
             } else {
                 // base 10 number
-                coords_as_u8[i] = match split.get(i).unwrap().parse::<u8>() {
-                    Ok(res) => res,
-                    Err(_) => {
-                        println!("The number {} can not be converted to a coordinate", {
-                            split.get(i).unwrap()
-                        });
-                        return (false, 0, 0);
-                    }
-                };
-            }
-        }
-        if !self.is_within_bounds(coords_as_u8[0], coords_as_u8[1]) {
-            return (false, 0, 0);
-        };
-
-        // if self.get_distance_to(coords_as_u8[0], coords_as_u8[1]) > Self::MAX_DIST {
-        //     println!(
-        //         "You can't move that far! Movement is limited to {} blocs",
-        //         Self::MAX_DIST
-        //     );
-        //     return (false, 0, 0);
-        // }
-
-        return (true, coords_as_u8[0], coords_as_u8[1]);
-    }
-
-    fn is_within_bounds(&self, x: u8, y: u8) -> bool {
-        if x >= Self::BOARD_WIDTH {
-            println!("Please respect the map bounds!");
-            println!(
-                "Max width is {}, which is lower than your input of x={}",
-                Self::BOARD_WIDTH - 1,
-                x
-            );
-            return false;
-        } else if y >= Self::BOARD_HEIGHT {
-            println!("Please respect the map bounds!");
-            println!(
-                "Max height is {}, which is lower than your input of y={}",
-                Self::BOARD_HEIGHT - 1,
-                y
-            );
-            return false;
+                number.parse::<u8>()
+            };
+            *coord = parsed.map_err(|_| CoordError::Number(number.to_string()))?;
         }
-        return true;
-    }
-}
-// print functions
-impl Board {
-    /// Print the help
-    pub fn print_help() {
-        println!();
-        println!("[*] Search command");
-        println!(
-            "This will tell you how far away you are from the treasure.\n\
-    It will also mark the searched square.\n\
-    If you use this command on the treasure square, you win the game."
-        );
-        println!();
-
-        println!("[*] Move command");
-        println!(
-            "To move to a coordinate, please use one of the following formats:\
-                    \n\t1. [x,y]: x ϵ [0;{0}), y ϵ [0;{1})\
-                    \n\t2. (x,y): x ϵ [0;{0}), y ϵ [0;{1})\
-                    \n\t[*] To use hex numbers, prefix them with '0x'.",
-            Board::BOARD_WIDTH,
-            Board::BOARD_HEIGHT
-        );
-
-        println!("[*] Help command");
-        println!("Shows this help.");
-        println!();
+        self.is_within_bounds(coords_as_u8[0], coords_as_u8[1])?;
 
-        println!("[*] Quit command");
-        println!("Leave the program.");
-        println!();
+        Ok((coords_as_u8[0], coords_as_u8[1]))
     }
 
-    /// Prints the `Board` to `stdout`.
-    ///
-    /// When the function returns, the terminal color is `White`.
-    /// This functions requires definition of the `BOARD_WIDTH`, `BOARD_HEIGHT` and `BOARD_COLOR` constants
-    pub fn print(&self) -> Result<()> {
-        let buffer_writer = BufferWriter::stdout(ColorChoice::Always);
-        let mut buffer = buffer_writer.buffer();
-
-        // Top row
-        buffer.set_color(ColorSpec::new().set_fg(Some(Self::BOARD_COLOR)))?;
-        write!(&mut buffer, "{:>4}", "#")?;
-        for _ in 0..Self::BOARD_WIDTH {
-            write!(&mut buffer, "###")?;
-        }
-        writeln!(&mut buffer, "#")?;
-
-        // Main grid
-        for y in (0..Self::BOARD_HEIGHT).rev() {
-            write!(&mut buffer, "{:>2} #", y)?; // Side coordinates
-
-            for x in 0..Self::BOARD_WIDTH {
-                let mut grid_c = self.board[x as usize][y as usize];
-                let dist = self.get_distance_to(x, y);
-                if grid_c == Self::SEARCHED_CHAR {
-                    buffer.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
-                }
-                // depending on your terminal you will not see much difference
-                if dist <= Self::MAX_DIST {
-                    buffer.set_color(ColorSpec::new().set_fg(Some(Color::Rgb(102, 255, 255))))?;
-                }
-                if dist <= Self::MAX_DIST / 2 {
-                    buffer.set_color(ColorSpec::new().set_fg(Some(Color::Rgb(0, 255, 255))))?;
-                }
-                if dist == 1 {
-                    buffer.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)))?;
-                }
-                if (x, y) == self.player.position.as_tuple() {
-                    buffer.set_color(ColorSpec::new().set_fg(Some(self.player.color)))?;
-                    grid_c = Self::PLAYER_CHAR;
-                }
-                write!(&mut buffer, "{:^3}", grid_c)?;
-                buffer.set_color(ColorSpec::new().set_fg(Some(Self::BOARD_COLOR)))?;
-            }
-
-            writeln!(&mut buffer, "#")?; // Side column
-        }
-
-        // Bottom row
-        write!(&mut buffer, "{:>4}", "#")?;
-        for _ in 0..Self::BOARD_WIDTH {
-            write!(&mut buffer, "###")?;
-        }
-        writeln!(&mut buffer, "#")?;
-
-        // Bottom coordinates
-        write!(&mut buffer, "{:4}", "")?;
-        for x in 0..Self::BOARD_WIDTH {
-            write!(&mut buffer, "{:^3}", x)?;
+    /// Checks that (x,y) is a square of the board
+    pub fn is_within_bounds(&self, x: u8, y: u8) -> Result<(), CoordError> {
+        if x >= self.config.width {
+            return Err(CoordError::OutOfBounds {
+                axis: Axis::X,
+                max: self.config.width - 1,
+                got: x,
+            });
+        } else if y >= self.config.height {
+            return Err(CoordError::OutOfBounds {
+                axis: Axis::Y,
+                max: self.config.height - 1,
+                got: y,
+            });
         }
-        writeln!(&mut buffer)?;
-
-        writeln!(&mut buffer)?;
-        buffer.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
-        return buffer_writer.print(&buffer);
+        Ok(())
     }
 }
-
 #[cfg(test)]
 mod test {
     use super::*;
@@ -361,8 +911,23 @@ mod test {
         ::trace
     )]
     fn within_bounds_tests(x: u8, y: u8, expected: bool) {
-        let board = Board::new();
-        assert_eq!(board.is_within_bounds(x, y), expected);
+        let board = Board::new(BoardConfig::default());
+        assert_eq!(board.is_within_bounds(x, y).is_ok(), expected);
+    }
+
+    #[rstest(
+        x,
+        y,
+        expected,
+        case(7, 19, true), // edge case
+        case(8, 0, false), // Out of bounds on x only
+        case(0, 20, false), // Out of bounds on y only
+        case(19, 7, false), // swapped dimensions
+        ::trace
+    )]
+    fn within_custom_bounds_tests(x: u8, y: u8, expected: bool) {
+        let board = Board::new(BoardConfig::new(8, 20, 4));
+        assert_eq!(board.is_within_bounds(x, y).is_ok(), expected);
     }
 
     #[rstest(
@@ -391,14 +956,539 @@ mod test {
     ::trace
     )]
     fn coord_input_validation(input: &str, expected: (bool, u8, u8)) {
-        let board = Board::new();
-        assert_eq!(board.validate_move_coordinates(input), expected);
+        let board = Board::new(BoardConfig::default());
+        let result = board
+            .validate_move_coordinates(input)
+            .map_or((false, 0, 0), |(x, y)| (true, x, y));
+        assert_eq!(result, expected);
+    }
+
+    #[rstest(
+    input,
+    expected,
+    case("", CoordError::Empty),
+    case("{13, 13}", CoordError::Format),
+    case("(13, 13]", CoordError::MismatchedBracket { open: '(', close: ')' }),
+    case("(,0x0)", CoordError::Dimensions { got: 1, expected: 2 }),
+    case("(0x100,0x0)", CoordError::Number("0x100".to_string())),
+    case("(3,15)", CoordError::OutOfBounds { axis: Axis::Y, max: 14, got: 15 }),
+    ::trace
+    )]
+    fn coord_input_errors(input: &str, expected: CoordError) {
+        let board = Board::new(BoardConfig::default());
+        assert_eq!(board.validate_move_coordinates(input), Err(expected));
+    }
+
+    #[test]
+    fn test_move() {
+        let mut board = Board::new(BoardConfig::default());
+        board.players[0].position.set((0, 0));
+        assert_eq!(board.move_to(5, 0), Err(MoveError::TooFar { max: 4 }));
+        assert_eq!(board.players[0].position.as_tuple(), (0, 0));
+        assert_eq!(board.move_to(4, 4), Ok(()));
+        assert_eq!(board.players[0].position.as_tuple(), (4, 4));
+        assert!(matches!(board.move_to(4, 15), Err(MoveError::Coord(_))));
+    }
+
+    #[test]
+    fn test_turn_log() {
+        let mut board = Board::new(BoardConfig::default());
+        board.treasures[0].position.set((6, 6));
+        board.players[0].position.set((0, 0));
+        board.players[0].start = board.players[0].position;
+        board.search();
+        assert!(board.move_to(9, 9).is_err()); // refused moves are not recorded
+        board.move_to(3, 4).unwrap();
+        board.move_to(6, 6).unwrap();
+        board.search();
+        assert_eq!(board.turns().len(), 4);
+        assert_eq!(
+            board.turns()[1],
+            Turn::Move {
+                from: Point { x: 0, y: 0 },
+                to: Point { x: 3, y: 4 },
+                distance: 4,
+                player: 0,
+                route: vec![
+                    Point { x: 1, y: 1 },
+                    Point { x: 2, y: 2 },
+                    Point { x: 2, y: 3 },
+                    Point { x: 3, y: 4 },
+                ],
+            }
+        );
+        let score = board.score(0);
+        assert_eq!(score.turns, 4);
+        assert_eq!(score.searches, 2);
+        assert_eq!(score.distance_walked, 7);
+        assert_eq!(score.optimal_turns, 3);
+    }
+
+    #[test]
+    fn test_move_metric() {
+        let config = BoardConfig::default().with_metric(Metric::Manhattan);
+        let mut board = Board::new(config);
+        board.players[0].position.set((0, 0));
+        // 3 blocs away diagonally, but 6 when only counting straight steps
+        assert_eq!(board.move_to(3, 3), Err(MoveError::TooFar { max: 4 }));
+        assert_eq!(board.move_to(1, 3), Ok(()));
+
+        board.treasures[0].position.set((1, 7));
+        assert_eq!(board.search(), SearchOutcome::Distance(4, None));
+    }
+
+    #[test]
+    fn test_compass_hints() {
+        let mut board = Board::new(BoardConfig::default().with_hints(HintMode::Compass));
+        board.treasures[0].position.set((10, 2));
+        board.players[0].position.set((2, 2));
+        assert_eq!(
+            board.search(),
+            SearchOutcome::Distance(8, Some(Hint::Direction(Direction::E)))
+        );
+        board.players[0].position.set((12, 12));
+        assert_eq!(
+            board.search(),
+            SearchOutcome::Distance(10, Some(Hint::Direction(Direction::S)))
+        );
+    }
+
+    #[test]
+    fn test_hot_cold_hints() {
+        let mut board = Board::new(BoardConfig::default().with_hints(HintMode::HotCold));
+        board.treasures[0].position.set((10, 10));
+        board.players[0].position.set((0, 0));
+        assert_eq!(board.search(), SearchOutcome::Distance(10, None));
+        board.move_to(4, 4).unwrap();
+        assert_eq!(
+            board.search(),
+            SearchOutcome::Distance(6, Some(Hint::Warmer))
+        );
+        board.move_to(4, 0).unwrap(); // moves do not reset the comparison
+        assert_eq!(
+            board.search(),
+            SearchOutcome::Distance(10, Some(Hint::Colder))
+        );
+        assert_eq!(
+            board.search(),
+            SearchOutcome::Distance(10, Some(Hint::Same))
+        );
+    }
+
+    #[test]
+    fn test_multiple_treasures() {
+        let config = BoardConfig::new(6, 6, 4).with_treasures(3);
+        let mut board = Board::from_seed(config, 9);
+        assert_eq!(board.treasures().len(), 3);
+        board.treasures[0].position.set((0, 0));
+        board.treasures[1].position.set((5, 5));
+        board.treasures[2].position.set((0, 5));
+
+        board.players[0].position.set((1, 1));
+        assert_eq!(board.search(), SearchOutcome::Distance(1, None));
+        board.move_to(0, 0).unwrap();
+        assert_eq!(board.search(), SearchOutcome::Found);
+        assert_eq!(board.treasures_left(), 2);
+        assert!(!board.is_treasure_found());
+        // a treasure can only be collected once, the next nearest one is now 5 blocs away
+        assert_eq!(board.search(), SearchOutcome::Distance(5, None));
+
+        board.move_to(0, 4).unwrap();
+        board.move_to(0, 5).unwrap();
+        assert_eq!(board.search(), SearchOutcome::Found);
+        board.move_to(4, 5).unwrap();
+        board.move_to(5, 5).unwrap();
+        assert_eq!(board.search(), SearchOutcome::Found);
+        assert!(board.is_treasure_found());
+        assert_eq!(board.score(0).searches, 5);
+    }
+
+    #[test]
+    fn test_turn_order() {
+        let config = BoardConfig::new(6, 6, 4).with_treasures(2).with_players(3);
+        let mut board = Board::from_seed(config, 9);
+        assert_eq!(board.players.len(), 3);
+        board.treasures[0].position.set((0, 0));
+        board.treasures[1].position.set((5, 5));
+        board.players[0].position.set((1, 1));
+        board.players[1].position.set((5, 4));
+        board.players[2].position.set((3, 3));
+
+        board.search();
+        assert_eq!(board.current_player(), 1);
+        // an invalid move does not end the turn
+        assert!(board.move_to(0, 0).is_err());
+        assert_eq!(board.current_player(), 1);
+        board.move_to(5, 5).unwrap();
+        assert_eq!(board.current_player(), 2);
+        board.search();
+        board.move_to(0, 0).unwrap();
+        assert_eq!(board.search(), SearchOutcome::Found);
+        assert_eq!(board.winner(), None);
+        board.search();
+        assert_eq!(board.search(), SearchOutcome::Found);
+        assert!(board.is_treasure_found());
+        // one treasure each, the second player found theirs first
+        assert_eq!(board.winner(), Some(1));
+        assert_eq!(board.current_player(), 0);
+        assert_eq!(board.score(0).turns, 3);
+        assert_eq!(board.score(1).turns, 2);
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let config = BoardConfig::new(6, 6, 2)
+            .with_treasures(2)
+            .with_players(2)
+            .with_practice(true);
+        let mut board = Board::from_seed(config, 4);
+        board.treasures[0].position.set((1, 1));
+        board.treasures[1].position.set((5, 5));
+        board.players[0].position.set((1, 1));
+        board.players[1].position.set((0, 0));
+        assert_eq!(board.undo(), Err(UndoError::NothingToUndo));
+
+        assert_eq!(board.search(), SearchOutcome::Found);
+        board.move_to(1, 1).unwrap();
+        assert_eq!(board.search(), SearchOutcome::Distance(4, None));
+
+        // the square stays marked while the first search is not taken back
+        assert!(matches!(board.undo(), Ok(Turn::Search { player: 0, .. })));
+        assert_eq!(board.board[1][1], Board::SEARCHED_CHAR);
+        assert!(matches!(board.undo(), Ok(Turn::Move { player: 1, .. })));
+        assert_eq!(board.players[1].position.as_tuple(), (0, 0));
+        assert_eq!(board.current_player(), 1);
+        assert!(matches!(board.undo(), Ok(Turn::Search { player: 0, .. })));
+        assert_eq!(board.board[1][1], Board::EMPTY_CHAR);
+        assert_eq!(board.treasures_left(), 2);
+        assert_eq!(board.current_player(), 0);
+        assert!(board.turns().is_empty());
+
+        assert_eq!(
+            board.redo(),
+            Ok(Turn::Search {
+                at: Point { x: 1, y: 1 },
+                outcome: SearchOutcome::Found,
+                player: 0,
+            })
+        );
+        assert_eq!(board.treasures_left(), 1);
+        assert!(matches!(board.redo(), Ok(Turn::Move { player: 1, .. })));
+        assert_eq!(board.current_player(), 0);
+
+        // a new turn forgets the turns taken back
+        board.move_to(2, 2).unwrap();
+        assert_eq!(board.redo(), Err(UndoError::NothingToRedo));
+        assert_eq!(board.turns().len(), 3);
+    }
+
+    #[test]
+    fn test_drift() {
+        let config = BoardConfig::new(6, 6, 2)
+            .with_drift(Some(2))
+            .with_practice(true);
+        let play = || {
+            let mut board = Board::from_seed(config, 4);
+            board.treasures[0].position.set((3, 3));
+            board.players[0].position.set((0, 0));
+            board.search();
+            assert_eq!(board.treasures[0].position.as_tuple(), (3, 3));
+            board.search();
+            board
+        };
+        let mut board = play();
+        let drifted = board.treasures[0].position;
+        assert_eq!(board.drift_count(), 1);
+        assert_eq!(
+            drifted.distance_to(&Point { x: 3, y: 3 }, Metric::Chebyshev),
+            1
+        );
+        // the drift only depends on the seed and the turns
+        assert_eq!(play().treasures[0].position, drifted);
+
+        board.undo().unwrap();
+        assert_eq!(board.treasures[0].position.as_tuple(), (3, 3));
+        assert!(board.drifts.is_empty());
+        board.redo().unwrap();
+        assert_eq!(board.treasures[0].position, drifted);
+    }
+
+    #[test]
+    fn test_score_after_drift() {
+        let mut board = Board::from_seed(BoardConfig::new(6, 6, 1).with_drift(Some(1)), 4);
+        board.treasures[0].position.set((3, 3));
+        board.players[0].position.set((0, 0));
+        board.players[0].start.set((0, 0));
+        board.search();
+        board.search();
+        let drifted = board.treasures[0].position;
+        assert_ne!(
+            drifted.distance_to(&Point { x: 0, y: 0 }, Metric::Chebyshev),
+            3
+        );
+        // 3 moves to where the treasure was hidden and a search
+        assert_eq!(board.score(0).optimal_turns, 4);
+    }
+
+    #[test]
+    fn test_drift_avoids_searched_squares() {
+        let mut board = Board::from_seed(BoardConfig::new(6, 6, 2).with_drift(Some(1)), 4);
+        board.treasures[0].position.set((0, 0));
+        board.players[0].position.set((1, 1));
+        board.board[1][0] = Board::SEARCHED_CHAR;
+        board.board[0][1] = Board::WALL_CHAR;
+        board.search();
+        assert_eq!(board.treasures[0].position.as_tuple(), (0, 0));
+        assert!(board.drifts.is_empty());
+    }
+
+    #[test]
+    fn test_undo_scored_game() {
+        let mut board = Board::new(BoardConfig::default());
+        board.search();
+        assert_eq!(board.undo(), Err(UndoError::NotPractice));
+        assert_eq!(board.redo(), Err(UndoError::NotPractice));
+        assert_eq!(board.turns().len(), 1);
+    }
+
+    #[test]
+    fn test_visibility() {
+        let config = BoardConfig::new(8, 8, 3).with_visibility(Some(2));
+        let mut board = Board::from_seed(config, 2);
+        board.treasures[0].position.set((7, 7));
+        board.players[0].position.set((0, 0));
+        assert!(board.is_visible(0, 2, 2));
+        assert!(!board.is_visible(0, 3, 0));
+        board.search();
+        board.move_to(3, 3).unwrap();
+        // the searched square stays visible, the squares walked through do not
+        assert!(board.is_visible(0, 0, 0));
+        assert!(!board.is_visible(0, 0, 1));
+        assert!(board.is_visible(0, 5, 5));
+
+        board.players[0].position.set((7, 7));
+        board.search();
+        assert!(board.is_visible(0, 0, 1));
+        assert!(Board::new(BoardConfig::default()).is_visible(0, 14, 14));
+    }
+
+    #[rstest(
+        name,
+        difficulty,
+        case("easy", Difficulty::Easy),
+        case("normal", Difficulty::Normal),
+        case("hard", Difficulty::Hard),
+        case("insane", Difficulty::Insane),
+        ::trace
+    )]
+    fn test_difficulty(name: &str, difficulty: Difficulty) {
+        assert_eq!(name.parse::<Difficulty>(), Ok(difficulty));
+        assert_eq!(difficulty.to_string(), name);
+        let config = BoardConfig::default()
+            .with_players(2)
+            .with_obstacles(10)
+            .with_difficulty(difficulty);
+        assert_eq!((config.players, config.obstacles), (2, 10));
+        assert!(config.treasures as usize <= config.width as usize * config.height as usize);
+    }
+
+    #[test]
+    fn test_normal_difficulty_is_default() {
+        assert_eq!(
+            BoardConfig::default().with_difficulty(Difficulty::Normal),
+            BoardConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_stamina() {
+        let config = BoardConfig::new(8, 8, 4).with_stamina(Some(10));
+        let mut board = Board::from_seed(config, 2);
+        board.treasures[0].position.set((7, 7));
+        board.players[0].position.set((0, 0));
+        assert_eq!(board.stamina_left(0), Some(10));
+        board.search();
+        board.move_to(4, 0).unwrap();
+        assert_eq!(board.stamina_left(0), Some(3));
+        assert_eq!(board.move_to(4, 4), Err(MoveError::Exhausted { left: 3 }));
+        assert!(!board.is_over());
+        board.search();
+        assert_eq!(board.stamina_left(0), Some(0));
+        assert!(board.is_exhausted(0));
+        assert!(board.is_lost());
+        assert!(board.is_over());
+        assert_eq!(board.winner(), None);
+        assert_eq!(Board::new(BoardConfig::default()).stamina_left(0), None);
+    }
+
+    #[test]
+    fn test_exhausted_players_skipped() {
+        let config = BoardConfig::new(8, 8, 4)
+            .with_players(2)
+            .with_stamina(Some(6));
+        let mut board = Board::from_seed(config, 2);
+        board.treasures[0].position.set((7, 7));
+        board.players[0].position.set((0, 0));
+        board.players[1].position.set((0, 1));
+        board.search();
+        board.move_to(1, 1).unwrap();
+        board.search();
+        assert!(board.is_exhausted(0));
+        assert_eq!(board.current_player(), 1);
+        board.move_to(2, 1).unwrap();
+        assert_eq!(board.current_player(), 1);
+        assert!(!board.is_over());
+        board.search();
+        assert!(board.is_lost());
+    }
+
+    #[test]
+    fn test_move_around_walls() {
+        let mut board = Board::from_seed(BoardConfig::new(5, 5, 3).with_obstacles(10), 3);
+        board.board = vec![vec![Board::EMPTY_CHAR; 5]; 5];
+        for y in 0..4 {
+            board.board[1][y] = Board::WALL_CHAR;
+        }
+        board.players[0].position.set((0, 0));
+        assert_eq!(board.move_to(1, 1), Err(MoveError::Blocked));
+        assert_eq!(board.move_to(2, 0), Err(MoveError::NoPath { max: 3 }));
+        assert_eq!(board.move_to(0, 2), Ok(()));
+        // 2 blocs away in a straight line, 3 on foot around the top of the wall
+        assert_eq!(board.move_to(2, 4), Ok(()));
+        assert_eq!(
+            board.turns().last(),
+            Some(&Turn::Move {
+                from: Point { x: 0, y: 2 },
+                to: Point { x: 2, y: 4 },
+                distance: 3,
+                player: 0,
+                route: vec![
+                    Point { x: 0, y: 3 },
+                    Point { x: 1, y: 4 },
+                    Point { x: 2, y: 4 },
+                ],
+            })
+        );
+    }
+
+    #[rstest(
+        metric,
+        input,
+        expected,
+        case(Metric::Chebyshev, "N2 e", Ok(vec![(2, 3), (2, 4), (3, 4)])),
+        case(Metric::Chebyshev, "NE, SW2", Ok(vec![(3, 3), (2, 2), (1, 1)])),
+        case(Metric::Manhattan, "(2,3) (3,3)", Ok(vec![(2, 3), (3, 3)])),
+        case(Metric::Manhattan, "(2,2) [2,3], (2,4)", Ok(vec![(2, 3), (2, 4)])),
+        case(Metric::Chebyshev, "(4,4)", Ok(vec![(3, 3), (4, 4)])),
+        case(Metric::Chebyshev, "S3", Err(MoveError::OffBoard)),
+        case(Metric::Chebyshev, "up", Err(MoveError::Step("up".to_string()))),
+        case(Metric::Chebyshev, "N300", Err(MoveError::Step("N300".to_string()))),
+        case(Metric::Chebyshev, "(2,3) (2,x)", Err(MoveError::Coord(CoordError::Number("x".to_string())))),
+        case(Metric::Chebyshev, "(2,3) (9,3)", Err(MoveError::Coord(CoordError::OutOfBounds { axis: Axis::X, max: 4, got: 9 }))),
+        ::trace
+    )]
+    fn test_parse_move(metric: Metric, input: &str, expected: Result<Vec<(u8, u8)>, MoveError>) {
+        let mut board = Board::from_seed(BoardConfig::new(5, 5, 3).with_metric(metric), 1);
+        board.players[0].position.set((2, 2));
+        let route = board
+            .parse_move(input)
+            .map(|route| route.iter().map(Point::as_tuple).collect());
+        assert_eq!(route, expected);
+    }
+
+    #[rstest(
+        metric,
+        route,
+        expected,
+        case(Metric::Chebyshev, vec![(1, 1), (2, 2)], Ok(())),
+        case(Metric::Chebyshev, vec![], Err(MoveError::Stay)),
+        case(Metric::Chebyshev, vec![(1, 0), (3, 0)], Err(MoveError::NotAStep { at: Point { x: 3, y: 0 } })),
+        case(Metric::Manhattan, vec![(1, 1)], Err(MoveError::NotAStep { at: Point { x: 1, y: 1 } })),
+        case(Metric::Chebyshev, vec![(1, 0), (2, 0), (3, 0), (4, 0)], Err(MoveError::TooFar { max: 3 })),
+        // back and forth is within range in a straight line, not on foot
+        case(Metric::Chebyshev, vec![(1, 0), (0, 0), (1, 0), (0, 0)], Err(MoveError::TooFar { max: 3 })),
+        ::trace
+    )]
+    fn test_move_along(metric: Metric, route: Vec<(u8, u8)>, expected: Result<(), MoveError>) {
+        let mut board = Board::from_seed(BoardConfig::new(5, 5, 3).with_metric(metric), 1);
+        board.players[0].position.set((0, 0));
+        let route: Vec<Point> = route.into_iter().map(|(x, y)| Point { x, y }).collect();
+        assert_eq!(board.move_along(&route), expected);
+        if expected.is_ok() {
+            assert_eq!(Some(&board.players[0].position), route.last());
+            assert!(
+                matches!(board.turns().last(), Some(Turn::Move { route: r, .. }) if *r == route)
+            );
+        }
+    }
+
+    #[test]
+    fn test_obstacles_placed() {
+        let config = BoardConfig::new(10, 10, 3)
+            .with_treasures(3)
+            .with_players(2)
+            .with_obstacles(30);
+        let board = Board::from_seed(config, 8);
+        let obstacles = (0..10)
+            .flat_map(|x| (0..10).map(move |y| (x, y)))
+            .filter(|&(x, y)| !board.is_passable(x, y))
+            .count();
+        assert_eq!(obstacles, 30);
+        let spawn = board.players[0].position;
+        let walk = board.walking_distances(spawn, u16::MAX);
+        for p in board.treasures().iter().map(|t| t.position) {
+            assert!(walk[p.x as usize][p.y as usize].is_some());
+        }
+        // boards without obstacles are drawn as before
+        let plain = Board::from_seed(config.with_obstacles(0), 8);
+        assert_eq!(plain.treasures(), board.treasures());
+        assert_eq!(plain.players[1].position, board.players[1].position);
+    }
+
+    #[test]
+    fn test_treasures_capped_to_board() {
+        let board = Board::new(BoardConfig::new(2, 2, 4).with_treasures(10));
+        let mut positions: Vec<(u8, u8)> = board
+            .treasures()
+            .iter()
+            .map(|t| t.position.as_tuple())
+            .collect();
+        positions.sort_unstable();
+        assert_eq!(positions, [(0, 0), (0, 1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn test_search_outcome() {
+        let mut board = Board::new(BoardConfig::default());
+        board.treasures[0].position.set((3, 3));
+        board.players[0].position.set((0, 1));
+        assert_eq!(board.search(), SearchOutcome::Distance(3, None));
+        assert!(!board.is_treasure_found());
+        board.players[0].position.set((3, 3));
+        assert_eq!(board.search(), SearchOutcome::Found);
+        assert!(board.is_treasure_found());
+    }
+
+    #[test]
+    fn test_seeded_boards() {
+        let config = BoardConfig::new(64, 64, 4);
+        let a = Board::from_seed(config, 42);
+        let b = Board::from_seed(config, 42);
+        assert_eq!(a.treasures, b.treasures);
+        assert_eq!(a.players[0].position, b.players[0].position);
+
+        // a different seed should not give the same game on a board this large
+        let c = Board::from_seed(config, 43);
+        assert_ne!(
+            (a.treasures[0].position, a.players[0].position),
+            (c.treasures[0].position, c.players[0].position)
+        );
     }
 
     // Every search should only yield one searched square
     #[test]
     fn test_search() {
-        let mut board = Board::new();
+        let mut board = Board::new(BoardConfig::default());
         for l in &board.board {
             for c in l {
                 assert_ne!(c, &Board::SEARCHED_CHAR);
@@ -412,7 +1502,7 @@ mod test {
             }
         }
         assert_eq!(count, 1);
-        board.player.position.x = board.player.position.x + 1 % 15;
+        board.players[0].position.x = (board.players[0].position.x + 1) % board.config.width;
         board.search();
         count = 0;
         for l in &board.board {
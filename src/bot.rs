@@ -0,0 +1,331 @@
+//! Computer player
+//!
+//! The `Bot` only knows what a human player would: the board settings and the replies of its own
+//! searches. Each reply rules out the squares that can not hold a hidden treasure, and the bot
+//! walks towards the closest square left.
+
+use crate::board::Board;
+use crate::history::Turn;
+use crate::outcome::SearchOutcome;
+use crate::terrain;
+use crate::utils::Point;
+
+#[derive(Debug, Clone)]
+pub struct Bot {
+    /// Index of the player the bot controls in `Board::players`
+    player: usize,
+    /// `possible[x][y]` is false once (x,y) is known not to hide a treasure still to be found
+    possible: Vec<Vec<bool>>,
+    /// Drifts of the treasures already taken into account in `possible`
+    drifts: usize,
+}
+
+impl Bot {
+    /// A bot playing `player` on `board`, knowing nothing of the treasures yet but that they
+    /// are not hidden in the walls or the water
+    pub fn new(board: &Board, player: usize) -> Self {
+        let config = board.config();
+        Self {
+            player,
+            possible: (0..config.width)
+                .map(|x| {
+                    (0..config.height)
+                        .map(|y| board.is_passable(x, y))
+                        .collect()
+                })
+                .collect(),
+            drifts: board.drift_count(),
+        }
+    }
+
+    /// Index of the player the bot controls
+    pub fn player(&self) -> usize {
+        self.player
+    }
+
+    /// Squares that may still hide a treasure, according to the searches of the bot
+    pub fn candidates(&self) -> Vec<Point> {
+        let mut candidates = Vec::new();
+        for (x, column) in self.possible.iter().enumerate() {
+            for (y, possible) in column.iter().enumerate() {
+                if *possible {
+                    candidates.push(Point {
+                        x: x as u8,
+                        y: y as u8,
+                    });
+                }
+            }
+        }
+        candidates
+    }
+
+    /// Plays the turn of the bot and returns it as recorded in the turn log
+    ///
+    /// The bot searches every square it lands on, otherwise it moves towards the closest
+    /// candidate square, or searches where it stands when it is too tired to move. It must be
+    /// the turn of the bot's player and the game must not be over.
+    pub fn play(&mut self, board: &mut Board) -> Turn {
+        debug_assert_eq!(board.current_player(), self.player);
+        self.follow_drifts(board);
+        let position = board.players[self.player].position;
+        let step = if self.possible[position.x as usize][position.y as usize] {
+            position
+        } else {
+            self.step(board, position)
+        };
+        if step == position {
+            let outcome = board.search();
+            self.learn(board, position, outcome);
+        } else {
+            let (x, y) = step.as_tuple();
+            board
+                .move_to(x, y)
+                .expect("the bot only moves within range");
+        }
+        // the reply of a search is learnt before the treasures drift at the end of the turn
+        self.follow_drifts(board);
+        board.turns().last().unwrap().clone()
+    }
+
+    /// Catches up with the drifts of the treasures since the last turn of the bot
+    fn follow_drifts(&mut self, board: &Board) {
+        for _ in self.drifts..board.drift_count() {
+            self.spread(board);
+        }
+        self.drifts = board.drift_count();
+    }
+
+    /// Rules out the squares contradicting the `outcome` of a search at `at`
+    fn learn(&mut self, board: &Board, at: Point, outcome: SearchOutcome) {
+        self.possible[at.x as usize][at.y as usize] = false;
+        let distance = match outcome {
+            SearchOutcome::Found => return,
+            SearchOutcome::Distance(distance, _) => distance,
+        };
+        // the reply is the distance to the nearest hidden treasure: none is closer, and when a
+        // single one is left it is exactly that far
+        let exact = board.treasures_left() == 1;
+        let metric = board.config().metric;
+        for (x, column) in self.possible.iter_mut().enumerate() {
+            for (y, possible) in column.iter_mut().enumerate() {
+                let d = at.distance_to(
+                    &Point {
+                        x: x as u8,
+                        y: y as u8,
+                    },
+                    metric,
+                );
+                if d < distance || (exact && d != distance) {
+                    *possible = false;
+                }
+            }
+        }
+    }
+
+    /// Adds the squares a drifting treasure could have reached from a candidate square in a
+    /// single step
+    fn spread(&mut self, board: &Board) {
+        let metric = board.config().metric;
+        let before = self.possible.clone();
+        for (x, column) in before.iter().enumerate() {
+            for (y, _) in column.iter().enumerate().filter(|(_, possible)| **possible) {
+                let from = Point {
+                    x: x as u8,
+                    y: y as u8,
+                };
+                for to in terrain::steps(&board.board, metric, from) {
+                    if board.board[to.x as usize][to.y as usize] == Board::EMPTY_CHAR {
+                        self.possible[to.x as usize][to.y as usize] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Square within range of `from` that is the closest to the nearest candidate
+    fn step(&self, board: &Board, from: Point) -> Point {
+        let target = self
+            .candidates()
+            .into_iter()
+            .min_by_key(|c| closeness(board, &from, c))
+            // only a bug in the distances would leave no candidate, keep exploring then
+            .unwrap_or(from);
+        step_towards(board, from, target)
+    }
+}
+
+/// Distance between two squares on the board, ties on the board metric are broken with the
+/// straight line distance
+fn closeness(board: &Board, a: &Point, b: &Point) -> (u16, i32) {
+    let (dx, dy) = (a.x as i32 - b.x as i32, a.y as i32 - b.y as i32);
+    (a.distance_to(b, board.config().metric), dx * dx + dy * dy)
+}
+
+/// Square within range of `from` that is the closest to `target`, on foot when there are
+/// obstacles on the way
+///
+/// Every step gets strictly closer to the target, until it is reached. When stamina is limited
+/// the step leaves enough of it for a search, staying put if it can not.
+pub(crate) fn step_towards(board: &Board, from: Point, target: Point) -> Point {
+    let config = board.config();
+    let range = match board.stamina_left(board.current_player()) {
+        Some(left) => (config.max_dist as u16).min(left.saturating_sub(Board::SEARCH_COST)),
+        None => config.max_dist as u16,
+    };
+    let in_range = |square: &Point| from.distance_to(square, config.metric) <= range;
+    let squares: Vec<Point> = if config.obstacles > 0 {
+        // only the squares in range the fewest steps away from the target are worth comparing
+        let reach = board.walking_distances(from, range);
+        board.closest_on_foot(target, |square| {
+            in_range(square) && reach[square.x as usize][square.y as usize].is_some()
+        })
+    } else {
+        let max = range as u8;
+        let xs = from.x.saturating_sub(max)..=from.x.saturating_add(max).min(config.width - 1);
+        let ys = from.y.saturating_sub(max)..=from.y.saturating_add(max).min(config.height - 1);
+        xs.flat_map(|x| ys.clone().map(move |y| Point { x, y }))
+            .filter(in_range)
+            .collect()
+    };
+    // staying put wins the ties
+    let stay = squares.contains(&from).then_some(from);
+    stay.into_iter()
+        .chain(squares)
+        .min_by_key(|square| closeness(board, square, &target))
+        .unwrap_or(from)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::BoardConfig;
+    use crate::outcome::HintMode;
+    use crate::utils::Metric;
+    use rstest::rstest;
+
+    /// Lets the bot play alone until every treasure is found, checking it never rules out one
+    fn solve(config: BoardConfig, seed: u64) -> u32 {
+        let mut board = Board::from_seed(config, seed);
+        let mut bot = Bot::new(&board, 0);
+        let squares = config.width as u32 * config.height as u32;
+        let mut turns = 0;
+        while !board.is_treasure_found() {
+            bot.play(&mut board);
+            for treasure in board.treasures().iter().filter(|t| !t.found) {
+                assert!(bot.candidates().contains(&treasure.position));
+            }
+            turns += 1;
+            assert!(turns <= 2 * squares, "the bot is stuck");
+        }
+        turns
+    }
+
+    #[rstest(
+        metric,
+        case(Metric::Chebyshev),
+        case(Metric::Manhattan),
+        case(Metric::Euclidean),
+        ::trace
+    )]
+    fn bot_finds_treasure(metric: Metric) {
+        for seed in 0..20 {
+            solve(BoardConfig::new(15, 15, 4).with_metric(metric), seed);
+            solve(BoardConfig::new(7, 3, 1).with_metric(metric), seed);
+        }
+    }
+
+    #[rstest(
+        metric,
+        case(Metric::Chebyshev),
+        case(Metric::Manhattan),
+        case(Metric::Euclidean),
+        ::trace
+    )]
+    fn bot_walks_around_obstacles(metric: Metric) {
+        for seed in 0..20 {
+            solve(
+                BoardConfig::new(12, 12, 3)
+                    .with_metric(metric)
+                    .with_obstacles(30),
+                seed,
+            );
+        }
+    }
+
+    #[rstest(
+        metric,
+        case(Metric::Chebyshev),
+        case(Metric::Manhattan),
+        case(Metric::Euclidean),
+        ::trace
+    )]
+    fn bot_follows_drifting_treasures(metric: Metric) {
+        for seed in 0..20 {
+            solve(
+                BoardConfig::new(10, 10, 3)
+                    .with_metric(metric)
+                    .with_treasures(2)
+                    .with_obstacles(10)
+                    .with_drift(Some(3)),
+                seed,
+            );
+        }
+    }
+
+    #[test]
+    fn bot_runs_out_of_stamina() {
+        let config = BoardConfig::new(15, 15, 4).with_stamina(Some(20));
+        for seed in 0..20 {
+            let mut board = Board::from_seed(config, seed);
+            let mut bot = Bot::new(&board, 0);
+            while !board.is_over() {
+                bot.play(&mut board);
+                assert!(board.turns().len() <= 20, "the bot is stuck");
+            }
+        }
+    }
+
+    #[test]
+    fn bot_finds_every_treasure() {
+        let config = BoardConfig::new(10, 10, 3)
+            .with_treasures(4)
+            .with_hints(HintMode::HotCold);
+        for seed in 0..20 {
+            solve(config, seed);
+        }
+    }
+
+    #[test]
+    fn bot_narrows_down() {
+        let mut board = Board::from_seed(BoardConfig::new(5, 5, 4), 1);
+        board.treasures[0].position.set((4, 4));
+        board.players[0].position.set((0, 0));
+        let mut bot = Bot::new(&board, 0);
+        assert_eq!(
+            bot.play(&mut board),
+            Turn::Search {
+                at: Point { x: 0, y: 0 },
+                outcome: SearchOutcome::Distance(4, None),
+                player: 0,
+            }
+        );
+        // only the squares 4 blocs away are left, the closest being (0,4)
+        assert_eq!(bot.candidates().len(), 9);
+        assert!(matches!(
+            bot.play(&mut board),
+            Turn::Move {
+                to: Point { x: 0, y: 4 },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn bot_opponent() {
+        let mut board = Board::from_seed(BoardConfig::new(8, 8, 2).with_players(2), 3);
+        let mut bot = Bot::new(&board, 1);
+        board.search();
+        assert_eq!(bot.play(&mut board).player(), 1);
+        assert_eq!(board.current_player(), 0);
+    }
+}
@@ -0,0 +1,501 @@
+//! Commands typed by the player at the start of a turn
+//!
+//! A command fits on one line: its name, one of its aliases or its number in the menu, followed
+//! by its argument. `move (3,4)`, `m 3 4`, `2` and `help move` are all valid commands.
+//! The `Registry` knows every name, players can add their own aliases in `ALIASES_FILE`.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Default aliases file, a JSON object mapping each alias to a command, such as
+/// `{ "go": "move", "dig": "search" }`
+pub const ALIASES_FILE: &str = "lab01_aliases.json";
+
+/// Typos up to this many edits away from a known name get a suggestion, fewer for short words
+const MAX_SUGGESTION_EDITS: usize = 2;
+
+/// An action chosen by the player
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Move to the coordinates, in the syntax of `Board::validate_move_coordinates`, or ask for
+    /// them when None
+    Move(Option<String>),
+    Search,
+    /// Help on a single command, or on all of them
+    Help(Option<Topic>),
+    Quit,
+    /// Save the game to this path
+    Save(String),
+    /// Load the game from this path
+    Load(String),
+    Scores,
+    /// Take back the last turn of a practice game
+    Undo,
+    /// Play again the last turn taken back
+    Redo,
+}
+
+/// The commands, whichever name they were typed with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Topic {
+    Move,
+    Search,
+    Help,
+    Quit,
+    Save,
+    Load,
+    Scores,
+    Undo,
+    Redo,
+}
+
+impl Topic {
+    /// Name of the command in the menu
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topic::Move => "move",
+            Topic::Search => "search",
+            Topic::Help => "help",
+            Topic::Quit => "quit",
+            Topic::Save => "save",
+            Topic::Load => "load",
+            Topic::Scores => "scores",
+            Topic::Undo => "undo",
+            Topic::Redo => "redo",
+        }
+    }
+}
+
+/// Reasons for `Registry::parse` to reject a line
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandError {
+    /// Nothing was entered
+    Empty,
+    /// The first word is not a command, it may be a typo of the suggested name
+    Unknown {
+        word: String,
+        suggestion: Option<String>,
+    },
+    /// The command needs an argument, described here
+    MissingArgument(&'static str),
+    /// The command takes no argument
+    UnexpectedArgument(&'static str),
+    /// The alias is empty, contains a space or is already the name of another command
+    InvalidAlias(String),
+}
+
+/// Reasons for `Registry::load_aliases` to give up on the aliases file at `path`
+#[derive(Debug)]
+pub enum AliasesError {
+    Io {
+        path: PathBuf,
+        err: io::Error,
+    },
+    /// The file is not a JSON object of strings
+    Json {
+        path: PathBuf,
+        err: serde_json::Error,
+    },
+    /// An alias of the file can not be added, the aliases before it were
+    Invalid {
+        path: PathBuf,
+        err: CommandError,
+    },
+}
+
+impl fmt::Display for AliasesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AliasesError::Io { path, err } => write!(
+                f,
+                "Could not read the aliases file {}: {}",
+                path.display(),
+                err
+            ),
+            AliasesError::Json { path, err } => write!(
+                f,
+                "The aliases file {} is corrupted: {}",
+                path.display(),
+                err
+            ),
+            AliasesError::Invalid { path, err } => {
+                write!(f, "The aliases file {} is invalid: {}", path.display(), err)
+            }
+        }
+    }
+}
+
+impl Error for AliasesError {}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Empty => write!(f, "Please enter a command!"),
+            CommandError::Unknown {
+                word,
+                suggestion: Some(suggestion),
+            } => write!(
+                f,
+                "\"{}\" is not a command, did you mean \"{}\"?",
+                word, suggestion
+            ),
+            CommandError::Unknown {
+                word,
+                suggestion: None,
+            } => write!(
+                f,
+                "\"{}\" is not a command, enter a number from 1 to 9 or the command name displayed!",
+                word
+            ),
+            CommandError::MissingArgument(argument) => write!(f, "Please add {}!", argument),
+            CommandError::UnexpectedArgument(command) => {
+                write!(f, "The {} command takes no argument!", command)
+            }
+            CommandError::InvalidAlias(alias) => {
+                write!(f, "\"{}\" can not be used as an alias", alias)
+            }
+        }
+    }
+}
+
+impl Error for CommandError {}
+
+/// Every name a command is known by: its name, its menu number and its aliases
+#[derive(Debug, Clone)]
+pub struct Registry {
+    /// Lowercase names, sorted so that suggestions do not depend on the order of the aliases
+    names: BTreeMap<String, Topic>,
+}
+
+impl Registry {
+    /// The names of the menu and the built-in aliases
+    pub fn new() -> Self {
+        let builtin = [
+            ("1", Topic::Move),
+            ("m", Topic::Move),
+            ("2", Topic::Search),
+            ("s", Topic::Search),
+            ("3", Topic::Help),
+            ("h", Topic::Help),
+            ("?", Topic::Help),
+            ("4", Topic::Quit),
+            ("q", Topic::Quit),
+            ("exit", Topic::Quit),
+            ("5", Topic::Save),
+            ("6", Topic::Load),
+            ("7", Topic::Scores),
+            ("8", Topic::Undo),
+            ("u", Topic::Undo),
+            ("9", Topic::Redo),
+            ("r", Topic::Redo),
+        ];
+        let mut names: BTreeMap<String, Topic> = builtin
+            .iter()
+            .map(|(name, topic)| (name.to_string(), *topic))
+            .collect();
+        for topic in [
+            Topic::Move,
+            Topic::Search,
+            Topic::Help,
+            Topic::Quit,
+            Topic::Save,
+            Topic::Load,
+            Topic::Scores,
+            Topic::Undo,
+            Topic::Redo,
+        ]
+        .iter()
+        {
+            names.insert(topic.name().to_string(), *topic);
+        }
+        Self { names }
+    }
+
+    /// Makes `alias` another name of `command`, which can be any name already known
+    ///
+    /// The names already known can not be redefined.
+    pub fn add_alias(&mut self, alias: &str, command: &str) -> Result<(), CommandError> {
+        let alias = alias.trim().to_lowercase();
+        if alias.is_empty() || alias.contains(char::is_whitespace) {
+            return Err(CommandError::InvalidAlias(alias));
+        }
+        let topic = self.lookup(command)?;
+        if self.names.get(&alias).is_some_and(|known| *known != topic) {
+            return Err(CommandError::InvalidAlias(alias));
+        }
+        self.names.insert(alias, topic);
+        Ok(())
+    }
+
+    /// Adds the aliases of the file at `path`, a missing file adds none
+    pub fn load_aliases<P: AsRef<Path>>(&mut self, path: P) -> Result<(), AliasesError> {
+        let path = path.as_ref();
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => {
+                return Err(AliasesError::Io {
+                    path: path.to_path_buf(),
+                    err,
+                })
+            }
+        };
+        let aliases: BTreeMap<String, String> =
+            serde_json::from_str(&json).map_err(|err| AliasesError::Json {
+                path: path.to_path_buf(),
+                err,
+            })?;
+        for (alias, command) in &aliases {
+            self.add_alias(alias, command)
+                .map_err(|err| AliasesError::Invalid {
+                    path: path.to_path_buf(),
+                    err,
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Parses a line typed by the player
+    ///
+    /// The command is case insensitive, its argument is kept as is. Moves also accept the two
+    /// coordinates separated by a space, as in `m 3 4`.
+    pub fn parse(&self, line: &str) -> Result<Command, CommandError> {
+        let line = line.trim();
+        let (word, arg) = match line.split_once(char::is_whitespace) {
+            Some((word, arg)) => (word, arg.trim()),
+            None => (line, ""),
+        };
+        if word.is_empty() {
+            return Err(CommandError::Empty);
+        }
+        let topic = self.lookup(word)?;
+        let command = match topic {
+            Topic::Move if arg.is_empty() => Command::Move(None),
+            Topic::Move => Command::Move(Some(move_coordinates(arg))),
+            Topic::Help if arg.is_empty() => Command::Help(None),
+            Topic::Help => Command::Help(Some(self.lookup(arg)?)),
+            Topic::Save | Topic::Load if arg.is_empty() => {
+                return Err(CommandError::MissingArgument("the path of the save file"))
+            }
+            Topic::Save => Command::Save(arg.to_string()),
+            Topic::Load => Command::Load(arg.to_string()),
+            _ if !arg.is_empty() => return Err(CommandError::UnexpectedArgument(topic.name())),
+            Topic::Search => Command::Search,
+            Topic::Quit => Command::Quit,
+            Topic::Scores => Command::Scores,
+            Topic::Undo => Command::Undo,
+            Topic::Redo => Command::Redo,
+        };
+        Ok(command)
+    }
+
+    /// The command named `word`, whatever its case
+    fn lookup(&self, word: &str) -> Result<Topic, CommandError> {
+        let word = word.to_lowercase();
+        if let Some(topic) = self.names.get(&word) {
+            return Ok(*topic);
+        }
+        // the closest name, the first one in alphabetical order on a tie, short words being
+        // close to too many names to guess
+        let max_edits = MAX_SUGGESTION_EDITS.min(word.chars().count() / 2);
+        let suggestion = self
+            .names
+            .keys()
+            .map(|name| (edit_distance(&word, name), name))
+            .filter(|(edits, _)| *edits <= max_edits)
+            .min_by_key(|(edits, _)| *edits)
+            .map(|(_, name)| name.clone());
+        Err(CommandError::Unknown { word, suggestion })
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Number of characters to insert, delete or substitute to turn `a` into `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + (ca != *cb) as usize;
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Turns the "x y" shorthand into "(x,y)", other coordinates and routes are left to the board
+/// to validate
+fn move_coordinates(arg: &str) -> String {
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    let number = |word: &str| word.starts_with(|c: char| c.is_ascii_digit());
+    match words[..] {
+        [x, y] if number(x) && number(y) && !x.ends_with(',') => format!("({},{})", x, y),
+        _ => arg.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        input,
+        expected,
+        case("1", Command::Move(None)),
+        case("Search", Command::Search),
+        case("s", Command::Search),
+        case("Save game.json", Command::Save("game.json".to_string())),
+        case("6 saves/My Game.json", Command::Load("saves/My Game.json".to_string())),
+        case("Scores", Command::Scores),
+        case("undo", Command::Undo),
+        case("9", Command::Redo),
+        case("QUIT", Command::Quit),
+        case("q", Command::Quit),
+        case("move (3,4)", Command::Move(Some("(3,4)".to_string()))),
+        case("Move [3, 4]", Command::Move(Some("[3, 4]".to_string()))),
+        case("m 3 4", Command::Move(Some("(3,4)".to_string()))),
+        case("m 0x3 4", Command::Move(Some("(0x3,4)".to_string()))),
+        case("m N3 E1", Command::Move(Some("N3 E1".to_string()))),
+        case("m (1,2) (2,3)", Command::Move(Some("(1,2) (2,3)".to_string()))),
+        case("  move   ", Command::Move(None)),
+        case("help", Command::Help(None)),
+        case("help move", Command::Help(Some(Topic::Move))),
+        case("? 5", Command::Help(Some(Topic::Save))),
+        ::trace
+    )]
+    fn parse_tests(input: &str, expected: Command) {
+        assert_eq!(Registry::new().parse(input), Ok(expected));
+    }
+
+    /// An unknown command without suggestion
+    fn unknown(word: &str) -> CommandError {
+        CommandError::Unknown {
+            word: word.to_string(),
+            suggestion: None,
+        }
+    }
+
+    #[rstest(
+        input,
+        expected,
+        case("", CommandError::Empty),
+        case("Save", CommandError::MissingArgument("the path of the save file")),  // missing path
+        case("Load   ", CommandError::MissingArgument("the path of the save file")),  // missing path
+        case("Quit now", CommandError::UnexpectedArgument("quit")),  // no argument expected
+        case("0", unknown("0")),
+        case("dance", unknown("dance")),
+        case("help dance", unknown("dance")),
+        ::trace
+    )]
+    fn parse_errors(input: &str, expected: CommandError) {
+        assert_eq!(Registry::new().parse(input), Err(expected));
+    }
+
+    #[rstest(
+        input,
+        expected,
+        case("serch", "search"),
+        case("Mvoe", "move"),
+        case("sores", "scores"),
+        case("qiut", "quit"),
+        case("help lod", "load"),
+        ::trace
+    )]
+    fn suggestion_tests(input: &str, expected: &str) {
+        match Registry::new().parse(input) {
+            Err(CommandError::Unknown {
+                suggestion: Some(suggestion),
+                ..
+            }) => assert_eq!(suggestion, expected),
+            other => panic!("no suggestion: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn aliases() {
+        let mut registry = Registry::new();
+        registry.add_alias("Go", "move").unwrap();
+        registry.add_alias("dig", "S").unwrap();
+        assert_eq!(
+            registry.parse("GO 1 2"),
+            Ok(Command::Move(Some("(1,2)".to_string())))
+        );
+        assert_eq!(registry.parse("dig"), Ok(Command::Search));
+        assert_eq!(
+            registry.parse("help dig"),
+            Ok(Command::Help(Some(Topic::Search)))
+        );
+
+        assert_eq!(
+            registry.add_alias("q", "search"),
+            Err(CommandError::InvalidAlias("q".to_string()))
+        );
+        assert_eq!(
+            registry.add_alias("walk about", "move"),
+            Err(CommandError::InvalidAlias("walk about".to_string()))
+        );
+        assert_eq!(registry.add_alias("fly", "jump"), Err(unknown("jump")));
+    }
+
+    #[test]
+    fn aliases_file() {
+        let path = std::env::temp_dir().join(format!("lab01_aliases_{}.json", std::process::id()));
+        let mut registry = Registry::new();
+        registry.load_aliases(&path).unwrap();
+
+        fs::write(&path, r#"{ "go": "move", "dig": "search" }"#).unwrap();
+        registry.load_aliases(&path).unwrap();
+        assert_eq!(registry.parse("dig"), Ok(Command::Search));
+
+        fs::write(&path, r#"{ "move": "quit" }"#).unwrap();
+        let invalid = Registry::new().load_aliases(&path).unwrap_err();
+        fs::write(&path, r#"["go", "move"]"#).unwrap();
+        let corrupted = Registry::new().load_aliases(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            invalid,
+            AliasesError::Invalid {
+                err: CommandError::InvalidAlias(_),
+                ..
+            }
+        ));
+        assert_eq!(
+            invalid.to_string(),
+            format!(
+                "The aliases file {} is invalid: \"move\" can not be used as an alias",
+                path.display()
+            )
+        );
+        assert!(matches!(corrupted, AliasesError::Json { .. }));
+        assert!(corrupted
+            .to_string()
+            .starts_with(&format!("The aliases file {} is corrupted", path.display())));
+    }
+
+    #[rstest(
+        a,
+        b,
+        expected,
+        case("", "", 0),
+        case("move", "move", 0),
+        case("", "help", 4),
+        case("serch", "search", 1),
+        case("kitten", "sitting", 3),
+        ::trace
+    )]
+    fn edit_distance_tests(a: &str, b: &str, expected: usize) {
+        assert_eq!(edit_distance(a, b), expected);
+    }
+}
@@ -0,0 +1,132 @@
+//! Record of the actions taken during a game and the resulting score
+
+use serde::{Deserialize, Serialize};
+
+use crate::outcome::SearchOutcome;
+use crate::utils::Point;
+
+/// One action of a player, as recorded by the `Board`
+///
+/// `player` is the index of the player in `Board::players`, it is missing from save files
+/// written before multiplayer games and then defaults to the first player.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Turn {
+    /// The player moved from `from` to `to`, `distance` blocs away
+    Move {
+        from: Point,
+        to: Point,
+        distance: u8,
+        #[serde(default)]
+        player: usize,
+        /// The squares stepped on after `from`, the last one being `to`. Missing from save
+        /// files written before version 10
+        #[serde(default)]
+        route: Vec<Point>,
+    },
+    /// The player searched the square `at`
+    Search {
+        at: Point,
+        outcome: SearchOutcome,
+        #[serde(default)]
+        player: usize,
+    },
+}
+
+impl Turn {
+    /// Index of the player who took this turn
+    pub fn player(&self) -> usize {
+        match self {
+            Turn::Move { player, .. } | Turn::Search { player, .. } => *player,
+        }
+    }
+}
+
+/// Summary of a game, used to compare runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    /// Every move and search
+    pub turns: u32,
+    pub searches: u32,
+    /// Sum of the distances of every move, in blocs
+    pub distance_walked: u32,
+    /// Lower bound of the turns a player knowing where the treasures are would need from the
+    /// same start: the moves to reach the farthest treasure and one search per treasure
+    pub optimal_turns: u32,
+}
+
+impl Score {
+    /// Computes the score of the `turns` played on a board with the given move range,
+    /// `start_distance` being the distance between the spawn and the farthest treasure
+    pub fn new(turns: &[Turn], start_distance: u16, max_dist: u8, treasures: u32) -> Self {
+        let mut score = Score {
+            turns: turns.len() as u32,
+            searches: 0,
+            distance_walked: 0,
+            optimal_turns: (start_distance as u32).div_ceil(max_dist as u32) + treasures,
+        };
+        for turn in turns {
+            match turn {
+                Turn::Move { distance, .. } => score.distance_walked += *distance as u32,
+                Turn::Search { .. } => score.searches += 1,
+            }
+        }
+        score
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        start_distance,
+        max_dist,
+        treasures,
+        expected,
+        case(0, 4, 1, 1),  // spawned on the treasure
+        case(4, 4, 1, 2),  // edge case
+        case(5, 4, 1, 3),
+        case(14, 4, 1, 5),
+        case(14, 1, 1, 15),
+        case(14, 4, 3, 7),
+        ::trace
+    )]
+    fn optimal_turns_tests(start_distance: u16, max_dist: u8, treasures: u32, expected: u32) {
+        assert_eq!(
+            Score::new(&[], start_distance, max_dist, treasures).optimal_turns,
+            expected
+        );
+    }
+
+    #[test]
+    fn score_counts() {
+        let a = Point { x: 0, y: 0 };
+        let b = Point { x: 3, y: 2 };
+        let turns = [
+            Turn::Search {
+                at: a,
+                outcome: SearchOutcome::Distance(5, None),
+                player: 0,
+            },
+            Turn::Move {
+                from: a,
+                to: b,
+                distance: 3,
+                player: 0,
+                route: Vec::new(),
+            },
+            Turn::Move {
+                from: b,
+                to: a,
+                distance: 3,
+                player: 0,
+                route: Vec::new(),
+            },
+        ];
+        let score = Score::new(&turns, 5, 4, 1);
+        assert_eq!(score.turns, 3);
+        assert_eq!(score.searches, 1);
+        assert_eq!(score.distance_walked, 6);
+    }
+}
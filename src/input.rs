@@ -0,0 +1,77 @@
+//! Line based input of the game, typed on the terminal or read from a script
+//!
+//! Every answer of the player, from the menu to the colour, goes through `Input::read_line`, so
+//! that a game can be played from a file exactly as it would be typed.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::path::Path;
+
+pub struct Input {
+    reader: Box<dyn BufRead>,
+    /// Whether the lines read are printed after their prompt, so that the output of a script
+    /// reads like a game played on the terminal
+    echo: bool,
+}
+
+impl Input {
+    /// Reads the terminal, or the lines piped to the program
+    pub fn stdin() -> Self {
+        let echo = !io::stdin().is_terminal();
+        Self::new(BufReader::new(io::stdin()), echo)
+    }
+
+    /// Reads the commands of a script file, one per line
+    pub fn script<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?), true))
+    }
+
+    pub fn new<R: BufRead + 'static>(reader: R, echo: bool) -> Self {
+        Self {
+            reader: Box::new(reader),
+            echo,
+        }
+    }
+
+    /// Whether the lines are typed on the terminal, rather than read from a script or a pipe
+    pub fn is_interactive(&self) -> bool {
+        !self.echo
+    }
+
+    /// Prints `prompt` then reads the next line, without its line ending
+    ///
+    /// Returns None once the input is exhausted or can not be read anymore.
+    pub fn read_line(&mut self, prompt: &str) -> Option<String> {
+        print!("{}", prompt);
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) | Err(_) => {
+                println!();
+                return None;
+            }
+            Ok(_) => {}
+        }
+        let line = line.trim_end_matches(&['\r', '\n'][..]).to_string();
+        if self.echo {
+            println!("{}", line);
+        }
+        Some(line)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn read_until_eof() {
+        let mut input = Input::new(Cursor::new("move (3,4)\r\n\nsearch"), false);
+        assert_eq!(input.read_line("> ").as_deref(), Some("move (3,4)"));
+        assert_eq!(input.read_line("> ").as_deref(), Some(""));
+        assert_eq!(input.read_line("> ").as_deref(), Some("search"));
+        assert_eq!(input.read_line("> "), None);
+        assert_eq!(input.read_line("> "), None);
+    }
+}
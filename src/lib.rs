@@ -0,0 +1,38 @@
+//! Treasure hunt game engine
+//!
+//! The `Board` holds the grid, the players taking turns on it and the hidden treasures. Front
+//! ends (such as the `lab01` binary, the `tui` and the `net` server) drive it through
+//! `Board::move_to` and `Board::search` and validate the user input with
+//! `Board::validate_move_coordinates` and `Player::set_color`.
+//! The engine never prints, results and errors are returned as the types of `outcome` and the
+//! `presenter` module renders them on the terminal. Only the helpers reading the user input,
+//! `Input` and `utils::ask_for_action`, print their prompts.
+
+pub mod bench;
+pub mod board;
+pub mod bot;
+pub mod command;
+pub mod history;
+pub mod input;
+pub mod net;
+pub mod outcome;
+pub mod player;
+pub mod presenter;
+pub mod save;
+pub mod scores;
+pub mod terrain;
+pub mod tui;
+pub mod utils;
+
+pub use board::{Board, BoardConfig, Difficulty};
+pub use bot::Bot;
+pub use command::{AliasesError, Command, CommandError, Registry, Topic};
+pub use history::{Score, Turn};
+pub use input::Input;
+pub use outcome::{
+    Axis, ColorError, CoordError, Direction, Hint, HintMode, MoveError, SearchOutcome, UndoError,
+};
+pub use player::Player;
+pub use save::SaveError;
+pub use scores::{Category, Leaderboard, ScoreEntry, ScoresError};
+pub use utils::{check_color, Point};
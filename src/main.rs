@@ -1,79 +1,630 @@
-use std::io::{self, stdout, Write};
+use std::env;
+use std::io::{self, stdout};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process;
 
-use crate::board::Board;
+use termcolor::Color;
 
-mod board;
-mod player;
-mod utils;
+use lab01::bench::{GridSweep, RandomWalk, Strategy, Trilateration};
+use lab01::command::ALIASES_FILE;
+use lab01::scores::SCORES_FILE;
+use lab01::utils::Metric;
+use lab01::{
+    bench, net, presenter, tui, utils, Board, BoardConfig, Bot, Category, Command, Difficulty,
+    HintMode, Input, Leaderboard, Registry, ScoreEntry, Turn,
+};
+
+/// Number of games shown by the Scores command
+const TOP_SCORES: usize = 10;
+
+/// Games played per strategy and board by `lab01 bench`, unless --games is given
+const DEFAULT_BENCH_GAMES: u32 = 1000;
+
+/// Width and height of the boards compared by `lab01 bench`
+const BENCH_SIZES: [u8; 3] = [8, 15, 32];
+
+/// Highest percentage of walls and water, more would leave little room to walk
+const MAX_OBSTACLES: u8 = 50;
+
+/// Options overridden by a difficulty, the difficulty menu is skipped when one of them is given
+const DIFFICULTY_OPTIONS: [&str; 8] = [
+    "--difficulty",
+    "--width",
+    "--height",
+    "--max-dist",
+    "--metric",
+    "--hints",
+    "--treasures",
+    "--stamina",
+];
 
 fn main() {
-    // Start a game
-    let mut board = board::Board::new();
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!(
+                "Usage: lab01 [serve [--port N]] [--difficulty LEVEL] [--width N] [--height N] [--max-dist N] [--metric NAME] [--hints MODE] [--treasures N] [--obstacles PERCENT] [--fog N] [--stamina N] [--drift TURNS] [--players N] [--bot] [--practice] [--tui] [--seed N] [--scores FILE] [--aliases FILE] [--script FILE]\n       lab01 connect [--host HOST] [--port N]\n       lab01 bench [--games N] [--max-dist N] [--hints MODE] [--treasures N] [--obstacles PERCENT] [--seed N]"
+            );
+            process::exit(1);
+        }
+    };
+    match options.mode {
+        Mode::Local => play_local(options),
+        Mode::Serve => serve(options),
+        Mode::Bench => bench(&options),
+        Mode::Connect => {
+            let stdin = io::stdin();
+            if let Err(err) = net::connect(
+                (options.host.as_str(), options.port),
+                stdin.lock(),
+                &mut stdout(),
+            ) {
+                eprintln!(
+                    "Could not play on {}:{}: {}",
+                    options.host, options.port, err
+                );
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Compares the search strategies on square boards of every size of `BENCH_SIZES` and every
+/// metric, the other settings are taken from the command line
+fn bench(options: &Options) {
+    let seed = options.seed.unwrap_or_else(rand::random);
+    println!("Seed: {}", seed);
+    presenter::print_bench_header(options.games);
+    let mut strategies: [Box<dyn Strategy>; 3] = [
+        Box::new(RandomWalk::new()),
+        Box::new(GridSweep),
+        Box::new(Trilateration::default()),
+    ];
+    for &size in BENCH_SIZES.iter() {
+        for &metric in [Metric::Chebyshev, Metric::Manhattan, Metric::Euclidean].iter() {
+            let config = BoardConfig {
+                width: size,
+                height: size,
+                metric,
+                ..options.config
+            };
+            for strategy in strategies.iter_mut() {
+                let stats = bench::run(strategy.as_mut(), config, options.games, seed);
+                presenter::print_bench_row(&config, strategy.name(), &stats);
+            }
+        }
+    }
+}
+
+/// Hosts a game for the `lab01 connect` clients, until the program is killed
+fn serve(options: Options) {
+    let seed = options.seed.unwrap_or_else(rand::random);
+    println!("Seed: {}", seed);
+    let board = Board::from_seed(options.config, seed);
+    let result = TcpListener::bind(("0.0.0.0", options.port)).and_then(|listener| {
+        println!(
+            "Waiting for {} players on port {}",
+            board.players.len(),
+            options.port
+        );
+        net::serve(listener, board)
+    });
+    if let Err(err) = result {
+        eprintln!("Could not serve on port {}: {}", options.port, err);
+        process::exit(1);
+    }
+}
+
+/// Plays a game on this terminal, taking turns when there are several players
+fn play_local(options: Options) {
+    // Start a game, the seed is always shown so the game can be replayed with --seed
+    let seed = options.seed.unwrap_or_else(rand::random);
+    println!("Seed: {}", seed);
+    let mut input = match &options.script {
+        Some(path) => match Input::script(path) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("Could not read the script {}: {}", path.display(), err);
+                process::exit(1);
+            }
+        },
+        None => Input::stdin(),
+    };
+    // scripts and piped input play the normal difficulty, they were written without the menu
+    let mut config = options.config;
+    if !options.custom_board
+        && input.is_interactive()
+        && !ask_for_difficulty(&mut input, &mut config)
+    {
+        return;
+    }
+    let mut board = Board::from_seed(config, seed);
+    let mut registry = Registry::new();
+    if let Err(err) = registry.load_aliases(&options.aliases) {
+        presenter::print_error(&err);
+    }
 
     // print the rules
-    utils::print_rules()
+    presenter::print_rules(board.config())
         .map_err(|err| println!("{:?}", err))
         .ok();
 
-    ask_for_color(&mut board);
+    // the computer plays the last player
+    let mut bot = if options.bot {
+        Some(Bot::new(&board, board.players.len() - 1))
+    } else {
+        None
+    };
+    for player in 0..board.players.len() {
+        if bot.as_ref().is_some_and(|bot| bot.player() == player) {
+            pick_bot_color(&mut board, player);
+        } else if !ask_for_color(&mut input, &mut board, player) {
+            return;
+        }
+    }
 
-    // print the updated board at the start of the round + simple error handling
-    board.print().map_err(|err| println!("{:?}", err)).ok();
-    while !board.treasure_found {
-        let usr_input = utils::ask_for_action().to_lowercase();
-        match &*usr_input {
-            "1" | "move" => {
-                move_logic(&mut board);
+    if options.tui {
+        if let Err(err) = tui::run(&mut board, bot.as_mut()) {
+            eprintln!("Could not play full screen: {}", err);
+        }
+    } else {
+        // print the updated board at the start of the round + simple error handling
+        presenter::print_board(&board)
+            .map_err(|err| println!("{:?}", err))
+            .ok();
+        while !board.is_over() {
+            if let Some(bot) = bot
+                .as_mut()
+                .filter(|bot| bot.player() == board.current_player())
+            {
+                let turn = bot.play(&mut board);
+                presenter::print_bot_turn(&turn);
+                presenter::print_board(&board)
+                    .map_err(|err| println!("{:?}", err))
+                    .ok();
+                continue;
             }
-            "2" | "search" => {
-                board.search();
+            if board.players.len() > 1 {
+                presenter::print_turn(&board)
+                    .map_err(|err| println!("{:?}", err))
+                    .ok();
             }
-            "3" | "help" => Board::print_help(),
-            "4" | "quit" => break,
-            _ => {}
-        };
+            // the end of the input quits the game
+            let command = match utils::ask_for_action(&mut input, &registry) {
+                Some(command) => command,
+                None => break,
+            };
+            match command {
+                Command::Move(coords) => {
+                    if !move_logic(&mut input, &mut board, coords.as_deref().unwrap_or("")) {
+                        break;
+                    }
+                }
+                Command::Search => {
+                    let outcome = board.search();
+                    presenter::print_search_outcome(outcome, board.treasures_left())
+                        .map_err(|err| println!("{:?}", err))
+                        .ok();
+                }
+                Command::Help(topic) => presenter::print_help(board.config(), topic),
+                Command::Quit => break,
+                Command::Save(path) => match board.save(&path) {
+                    Ok(()) => println!("Game saved to {}", path),
+                    Err(err) => presenter::print_error(&err),
+                },
+                Command::Load(path) => match Board::load(&path) {
+                    Ok(loaded) => {
+                        board = loaded;
+                        if bot.is_some() {
+                            bot = Some(Bot::new(&board, board.players.len() - 1));
+                        }
+                        println!("Game loaded from {}", path);
+                        presenter::print_board(&board)
+                            .map_err(|err| println!("{:?}", err))
+                            .ok();
+                    }
+                    Err(err) => presenter::print_error(&err),
+                },
+                Command::Scores => show_scores(&options.scores, &category(&board)),
+                Command::Undo => match board.undo() {
+                    Ok(turn) => {
+                        presenter::print_undo(&board, &turn, false);
+                        presenter::print_board(&board)
+                            .map_err(|err| println!("{:?}", err))
+                            .ok();
+                    }
+                    Err(err) => presenter::print_error(&err),
+                },
+                Command::Redo => match board.redo() {
+                    Ok(turn) => {
+                        presenter::print_undo(&board, &turn, true);
+                        if let Turn::Search { outcome, .. } = turn {
+                            presenter::print_search_outcome(outcome, board.treasures_left())
+                                .map_err(|err| println!("{:?}", err))
+                                .ok();
+                        }
+                        presenter::print_board(&board)
+                            .map_err(|err| println!("{:?}", err))
+                            .ok();
+                    }
+                    Err(err) => presenter::print_error(&err),
+                },
+            };
+        }
+    }
+
+    if board.is_lost() {
+        presenter::print_defeat(&board);
+        presenter::print_board(&board)
+            .map_err(|err| println!("{:?}", err))
+            .ok();
+    }
+    if board.players.len() > 1 {
+        presenter::print_results(&board);
+        return;
+    }
+    presenter::print_score(&board.score(0));
+    // only the games of human players make it to the high scores, practice games do not count
+    if bot.is_some() || board.config().practice {
+        return;
+    }
+    if board.is_treasure_found() {
+        record_score(&mut input, &options.scores, &board);
+    }
+}
+
+/// Games are ranked against games played with the same board settings
+fn category(board: &Board) -> Category {
+    let config = board.config();
+    let mut difficulty = format!("range {}, {}", config.max_dist, config.metric);
+    if config.hints != HintMode::None {
+        difficulty.push_str(&format!(", {} hints", config.hints));
+    }
+    if config.treasures > 1 {
+        difficulty.push_str(&format!(", {} treasures", config.treasures));
+    }
+    if config.obstacles > 0 {
+        difficulty.push_str(&format!(", {}% obstacles", config.obstacles));
+    }
+    if let Some(radius) = config.visibility {
+        difficulty.push_str(&format!(", fog {}", radius));
+    }
+    if let Some(stamina) = config.stamina {
+        difficulty.push_str(&format!(", stamina {}", stamina));
+    }
+    if let Some(every) = config.drift {
+        difficulty.push_str(&format!(", drift {}", every));
+    }
+    Category::new(config, &difficulty)
+}
+
+fn show_scores(path: &PathBuf, category: &Category) {
+    match Leaderboard::load(path) {
+        Ok(leaderboard) => {
+            presenter::print_leaderboard(category, &leaderboard.top(category, TOP_SCORES))
+        }
+        Err(err) => presenter::print_error(&err),
+    }
+}
+
+/// Adds the won game to the leaderboard and shows the updated table
+fn record_score(input: &mut Input, path: &PathBuf, board: &Board) {
+    let mut leaderboard = match Leaderboard::load(path) {
+        Ok(leaderboard) => leaderboard,
+        Err(err) => {
+            presenter::print_error(&err);
+            return;
+        }
+    };
+    let name = match input.read_line("Enter your name for the high scores: ") {
+        Some(name) if !name.trim().is_empty() => name,
+        _ => return,
+    };
+    leaderboard.add(ScoreEntry::new(
+        name.trim(),
+        category(board),
+        &board.score(0),
+    ));
+    match leaderboard.save(path) {
+        Ok(()) => show_scores(path, &category(board)),
+        Err(err) => presenter::print_error(&err),
     }
 }
 
-fn move_logic(board: &mut Board) {
-    let mut result: (bool, u8, u8) = (false, 0, 0);
-    let mut coord = String::new();
-    while !result.0 {
+/// Moves the current player to `coords` when given with the command, otherwise asks for
+/// coordinates until they are valid
+///
+/// Returns false if the input ended before a valid move was entered.
+fn move_logic(input: &mut Input, board: &mut Board, coords: &str) -> bool {
+    let mut coord = coords.to_string();
+    loop {
+        if coord.is_empty() {
+            coord = match input.read_line("Move: ") {
+                Some(coord) => coord,
+                None => return false,
+            };
+        }
+        let moved = board
+            .parse_move(&coord)
+            .and_then(|route| board.move_along(&route));
+        match moved {
+            Ok(()) => break,
+            Err(err) => presenter::print_error(&err),
+        }
+        // a command of a script is not retried, the next line is the next command
+        if !coords.is_empty() {
+            return true;
+        }
         coord.clear();
-        print!("Move: ");
-        let _ = stdout().flush();
-        match io::stdin().read_line(&mut coord) {
-            Ok(_) => {}
-            Err(_) => println!("Something went wrong, couldn't get input."),
-        }
-        // Clear line return from the line read
-        coord = coord.trim_end_matches('\n').parse().unwrap();
-        result = board.validate_move_coordinates(coord.as_str());
-        if result.0 {
-            result.0 = board.get_distance_to(result.1, result.2) <= Board::MAX_DIST;
-            if !result.0 {
-                println!(
-                    "You can't move that far! Movement is limited to {} blocs",
-                    Board::MAX_DIST
-                );
+    }
+    presenter::print_board(board)
+        .map_err(|err| println!("{:?}", err))
+        .ok();
+    true
+}
+
+/// Asks for the difficulty of the game, an empty answer keeps the classic board of `config`
+///
+/// Returns false if the input ended before a difficulty was chosen.
+fn ask_for_difficulty(input: &mut Input, config: &mut BoardConfig) -> bool {
+    presenter::print_difficulties(config);
+    loop {
+        let answer =
+            match input.read_line("Choose a difficulty, by number or name (empty for normal): ") {
+                Some(answer) => answer.trim().to_lowercase(),
+                None => return false,
+            };
+        let difficulty = match answer.parse::<usize>() {
+            Ok(n) if (1..=Difficulty::ALL.len()).contains(&n) => Some(Difficulty::ALL[n - 1]),
+            _ if answer.is_empty() => Some(Difficulty::Normal),
+            _ => answer.parse::<Difficulty>().ok(),
+        };
+        match difficulty {
+            Some(difficulty) => {
+                *config = config.with_difficulty(difficulty);
+                return true;
+            }
+            None => println!("\"{}\" is not a difficulty.", answer),
+        }
+    }
+}
+
+/// Asks `player` for their colour, it must differ from the colours picked by the players before
+///
+/// Returns false if the input ended before a colour was chosen.
+fn ask_for_color(input: &mut Input, board: &mut Board, player: usize) -> bool {
+    let prompt = format!(
+        "{}Please choose your player colour, either in English or with an RGB value (e.g.: Green, 133,230,89): ",
+        if board.players.len() > 1 {
+            format!("Player {}: ", player + 1)
+        } else {
+            String::new()
+        }
+    );
+    loop {
+        let usr_color = match input.read_line(&prompt) {
+            Some(color) => color,
+            None => return false,
+        };
+        match board.players[player].set_color(&usr_color) {
+            Ok(()) => {
+                let color = board.players[player].color;
+                if board.players[..player].iter().all(|p| p.color != color) {
+                    return true;
+                }
+                println!("This colour is already taken.");
+            }
+            Err(err) => presenter::print_error(&err),
+        }
+    }
+}
+
+/// Gives the computer player the first colour not taken by the players before it
+fn pick_bot_color(board: &mut Board, player: usize) {
+    const COLORS: [Color; 4] = [Color::Magenta, Color::Cyan, Color::Yellow, Color::Green];
+    let taken: Vec<Color> = board.players[..player].iter().map(|p| p.color).collect();
+    if let Some(color) = COLORS.iter().find(|color| !taken.contains(color)) {
+        board.players[player].color = *color;
+    }
+}
+
+/// Whether the game is played on this terminal or over the network
+#[derive(Debug, PartialEq)]
+enum Mode {
+    Local,
+    Serve,
+    Connect,
+    Bench,
+}
+
+/// Settings of a game given on the command line
+struct Options {
+    mode: Mode,
+    config: BoardConfig,
+    seed: Option<u64>,
+    /// Whether the computer plays the last player
+    bot: bool,
+    /// Whether the game is played full screen
+    tui: bool,
+    /// Whether the board was set up on the command line, otherwise the player picks a
+    /// difficulty when the game starts
+    custom_board: bool,
+    /// Aliases of the commands
+    aliases: PathBuf,
+    /// File the commands are read from instead of the terminal
+    script: Option<PathBuf>,
+    /// Games played per strategy and board by the benchmark
+    games: u32,
+    /// Leaderboard file
+    scores: PathBuf,
+    /// Server to connect to
+    host: String,
+    port: u16,
+}
+
+/// Builds the `Options` from the command line arguments
+///
+/// Every option is optional, missing ones keep the default 15x15 board with a 4 blocs move limit,
+/// or the settings of `--difficulty`, and a random seed. A leading `serve` or `connect` plays
+/// over the network.
+fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut args = args.peekable();
+    let mode = match args.peek().map(String::as_str) {
+        Some("serve") => Mode::Serve,
+        Some("connect") => Mode::Connect,
+        Some("bench") => Mode::Bench,
+        _ => Mode::Local,
+    };
+    if mode != Mode::Local {
+        args.next();
+    }
+    let mut options = Options {
+        mode,
+        config: BoardConfig::default(),
+        seed: None,
+        bot: false,
+        tui: false,
+        custom_board: false,
+        aliases: PathBuf::from(ALIASES_FILE),
+        script: None,
+        games: DEFAULT_BENCH_GAMES,
+        scores: PathBuf::from(SCORES_FILE),
+        host: "127.0.0.1".to_string(),
+        port: net::DEFAULT_PORT,
+    };
+    let mut settings = Vec::new();
+    while let Some(arg) = args.next() {
+        // flags take no value
+        match arg.as_str() {
+            "--bot" => {
+                options.bot = true;
+                continue;
+            }
+            "--tui" => {
+                options.tui = true;
+                continue;
+            }
+            "--practice" => {
+                options.config.practice = true;
+                continue;
             }
+            _ => {}
+        }
+        let value = args.next().ok_or(format!("Missing value for {}", arg))?;
+        settings.push((arg, value));
+    }
+    // the difficulty is the base of the other board settings, wherever it is given
+    settings.sort_by_key(|(arg, _)| arg != "--difficulty");
+    for (arg, value) in settings {
+        if DIFFICULTY_OPTIONS.contains(&arg.as_str()) {
+            options.custom_board = true;
         }
+        match arg.as_str() {
+            "--difficulty" => {
+                let difficulty = value
+                    .parse::<Difficulty>()
+                    .map_err(|_| format!("{} expects easy, normal, hard or insane", arg))?;
+                options.config = options.config.with_difficulty(difficulty);
+            }
+            "--width" => options.config.width = parse_dimension(&arg, &value)?,
+            "--height" => options.config.height = parse_dimension(&arg, &value)?,
+            "--max-dist" => options.config.max_dist = parse_dimension(&arg, &value)?,
+            "--treasures" => {
+                options.config.treasures = parse_dimension(&arg, &value)?;
+            }
+            "--obstacles" => {
+                options.config.obstacles = match value.parse::<u8>() {
+                    Ok(n) if n <= MAX_OBSTACLES => n,
+                    _ => {
+                        return Err(format!(
+                            "{} expects a percentage between 0 and {}",
+                            arg, MAX_OBSTACLES
+                        ))
+                    }
+                }
+            }
+            "--fog" => {
+                options.config.visibility = Some(
+                    value
+                        .parse::<u8>()
+                        .map_err(|_| format!("{} expects a number between 0 and 255", arg))?,
+                )
+            }
+            "--stamina" => {
+                options.config.stamina = match value.parse::<u16>() {
+                    Ok(n) if n >= Board::SEARCH_COST => Some(n),
+                    _ => {
+                        return Err(format!(
+                            "{} expects a number between {} and {}",
+                            arg,
+                            Board::SEARCH_COST,
+                            u16::MAX
+                        ))
+                    }
+                }
+            }
+            "--drift" => {
+                options.config.drift = Some(parse_dimension(&arg, &value)?);
+            }
+            "--players" => {
+                options.config.players = match value.parse::<u8>() {
+                    Ok(n) if (1..=4).contains(&n) => n,
+                    _ => return Err(format!("{} expects a number between 1 and 4", arg)),
+                }
+            }
+            "--metric" => {
+                options.config.metric = value
+                    .parse::<Metric>()
+                    .map_err(|_| format!("{} expects chebyshev, manhattan or euclidean", arg))?
+            }
+            "--hints" => {
+                options.config.hints = value
+                    .parse::<HintMode>()
+                    .map_err(|_| format!("{} expects none, compass or hotcold", arg))?
+            }
+            "--seed" => {
+                options.seed = Some(
+                    value
+                        .parse::<u64>()
+                        .map_err(|_| format!("{} expects a positive integer", arg))?,
+                )
+            }
+            "--scores" => options.scores = PathBuf::from(value),
+            "--script" => options.script = Some(PathBuf::from(value)),
+            "--aliases" => options.aliases = PathBuf::from(value),
+            "--host" => options.host = value,
+            "--games" => {
+                options.games = value
+                    .parse::<u32>()
+                    .map_err(|_| format!("{} expects a positive integer", arg))?
+            }
+            "--port" => {
+                options.port = value
+                    .parse::<u16>()
+                    .map_err(|_| format!("{} expects a number between 0 and 65535", arg))?
+            }
+            _ => return Err(format!("Unknown argument \"{}\"", arg)),
+        }
+    }
+    let config = &options.config;
+    // the computer player would keep what it learnt from the turns taken back
+    if config.practice && options.bot {
+        return Err("--practice can not be combined with --bot".to_string());
+    }
+    if config.treasures as usize > config.width as usize * config.height as usize {
+        return Err(format!(
+            "{} treasures can not be hidden on a {}x{} board",
+            config.treasures, config.width, config.height
+        ));
     }
-    board.move_to(result.1, result.2);
-    board.print().map_err(|err| println!("{:?}", err)).ok();
+    Ok(options)
 }
 
-fn ask_for_color(board: &mut Board) {
-    let mut usr_color = String::new();
-    while !board.player.set_color(usr_color.as_str()) {
-        usr_color.clear();
-        print!("Please choose your player colour, either in English or with an RGB value (e.g.: Green, 133,230,89): ");
-        let _ = stdout().flush();
-        match io::stdin().read_line(&mut usr_color) {
-            Ok(_) => {}
-            Err(_) => println!("Something went wrong, couldn't get input"),
-        }
-        // Clear line return from the line read
-        usr_color = usr_color.trim_end_matches('\n').parse().unwrap();
+fn parse_dimension(arg: &str, value: &str) -> Result<u8, String> {
+    match value.parse::<u8>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("{} expects a number between 1 and 255", arg)),
     }
 }
@@ -0,0 +1,355 @@
+//! Playing a `Board` across machines over TCP
+//!
+//! `serve` hosts a single game and every connected client controls one of its players. The
+//! protocol is line based, the client sends one command per line:
+//!
+//! - `MOVE (x,y)`: moves the player, with the syntax of `Board::parse_move`
+//! - `SEARCH`: searches the square of the player
+//! - `STATE`: whose turn it is followed by the grid as seen by the player, top row first, the
+//!   squares hidden by the fog of war being `?`
+//!
+//! Each answer of the server starts with `OK` or `ERR` and ends with an empty line. Clients are
+//! greeted with an answer telling them which player they control as soon as they connect.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::board::Board;
+use crate::outcome::SearchOutcome;
+
+/// Port used when none is given on the command line
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Squares hidden by the fog of war in a `STATE` answer, unlike `Board::FOG_CHAR` a fully fogged
+/// row is not mistaken for the empty line ending the answer
+pub const FOG_SYMBOL: char = '?';
+
+/// The game shared by the clients of a server
+struct Game {
+    board: Board,
+    /// Whether a client currently controls the player at the same index
+    connected: Vec<bool>,
+}
+
+/// Hosts `board` for the clients connecting to `listener`, one thread per client
+///
+/// A client takes the first player no other client controls, and gives it back when it
+/// disconnects. Clients connecting while every player is taken are turned away.
+/// Only returns if the listener fails.
+pub fn serve(listener: TcpListener, board: Board) -> io::Result<()> {
+    let connected = vec![false; board.players.len()];
+    let game = Arc::new(Mutex::new(Game { board, connected }));
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let game = Arc::clone(&game);
+        thread::spawn(move || {
+            // a client leaving abruptly only ends its own connection
+            let _ = handle_client(stream, &game);
+        });
+    }
+    Ok(())
+}
+
+fn handle_client(stream: TcpStream, game: &Mutex<Game>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let (player, players) = {
+        let mut game = game.lock().unwrap();
+        match game.connected.iter().position(|connected| !connected) {
+            Some(player) => {
+                game.connected[player] = true;
+                (player, game.connected.len())
+            }
+            None => return write_answer(&mut writer, "ERR the game is full"),
+        }
+    };
+    let result = write_answer(
+        &mut writer,
+        &format!("OK you are player {} of {}", player + 1, players),
+    )
+    .and_then(|()| {
+        for line in BufReader::new(stream).lines() {
+            let answer = answer(&mut game.lock().unwrap().board, player, &line?);
+            write_answer(&mut writer, &answer)?;
+        }
+        Ok(())
+    });
+    game.lock().unwrap().connected[player] = false;
+    result
+}
+
+fn write_answer<W: Write>(writer: &mut W, answer: &str) -> io::Result<()> {
+    write!(writer, "{}\n\n", answer)?;
+    writer.flush()
+}
+
+/// Runs a command sent by the client controlling `player` and returns the answer of the server,
+/// without the final empty line
+pub fn answer(board: &mut Board, player: usize, line: &str) -> String {
+    let line = line.trim();
+    let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+    match &*command.to_uppercase() {
+        "STATE" => state(board, player),
+        "MOVE" | "SEARCH" if board.is_over() => "ERR the game is over".to_string(),
+        "MOVE" | "SEARCH" if board.current_player() != player => format!(
+            "ERR it is the turn of player {}",
+            board.current_player() + 1
+        ),
+        "MOVE" => {
+            let moved = board
+                .parse_move(args)
+                .and_then(|route| board.move_along(&route));
+            match moved {
+                Ok(()) => {
+                    let to = board.players[player].position;
+                    format!("OK moved to ({},{})", to.x, to.y)
+                }
+                Err(err) => error(&err),
+            }
+        }
+        "SEARCH" => match board.search() {
+            SearchOutcome::Found => match board.winner() {
+                Some(winner) => {
+                    format!("OK you found the last treasure, player {} wins", winner + 1)
+                }
+                None => format!("OK you found a treasure, {} left", board.treasures_left()),
+            },
+            SearchOutcome::Distance(distance, None) => {
+                format!("OK the treasure is {} blocs away", distance)
+            }
+            SearchOutcome::Distance(distance, Some(hint)) => {
+                format!("OK the treasure is {} blocs away, {}", distance, hint)
+            }
+        },
+        _ => format!(
+            "ERR unknown command \"{}\", expected MOVE (x,y), SEARCH or STATE",
+            command
+        ),
+    }
+}
+
+/// Errors are sent on a single line
+fn error<E: ToString>(err: &E) -> String {
+    format!("ERR {}", err.to_string().replace('\n', " "))
+}
+
+/// Whose turn it is and the stamina left of `player`, then one line per row of the grid with the
+/// players shown by their number
+///
+/// The squares `player` can not see through the fog of war are `FOG_SYMBOL`, the treasures left are
+/// revealed once the game is lost.
+fn state(board: &Board, player: usize) -> String {
+    let config = board.config();
+    let mut state = match board.winner() {
+        Some(winner) => format!("OK game over, player {} wins", winner + 1),
+        None if board.is_lost() => "OK game over, every player is out of stamina".to_string(),
+        None => format!(
+            "OK player {} to play, {} treasures left",
+            board.current_player() + 1,
+            board.treasures_left()
+        ),
+    };
+    if let (false, Some(left)) = (board.is_over(), board.stamina_left(player)) {
+        state.push_str(&format!(", {} stamina left", left));
+    }
+    for y in (0..config.height).rev() {
+        state.push('\n');
+        for x in 0..config.width {
+            if !board.is_visible(player, x, y) {
+                state.push(FOG_SYMBOL);
+                continue;
+            }
+            let mut c = board.board[x as usize][y as usize];
+            if board
+                .treasures()
+                .iter()
+                .any(|t| (t.found || board.is_lost()) && t.position.as_tuple() == (x, y))
+            {
+                c = Board::TREASURE_CHAR;
+            }
+            if let Some(player) = board
+                .players
+                .iter()
+                .position(|p| p.position.as_tuple() == (x, y))
+            {
+                c = std::char::from_digit(player as u32 + 1, 10).unwrap_or(Board::PLAYER_CHAR);
+            }
+            state.push(c);
+        }
+    }
+    state
+}
+
+/// Plays on the server at `addr`: sends every line of `input` as a command and copies the
+/// answers to `output`, until `input` ends, a `QUIT` line is read or the server disconnects
+pub fn connect<A: ToSocketAddrs, R: BufRead, W: Write>(
+    addr: A,
+    input: R,
+    output: &mut W,
+) -> io::Result<()> {
+    let stream = TcpStream::connect(addr)?;
+    let mut writer = stream.try_clone()?;
+    let mut answers = BufReader::new(stream);
+    match read_answer(&mut answers)? {
+        Some(greeting) => {
+            output.write_all(greeting.as_bytes())?;
+            if greeting.starts_with("ERR") {
+                return Ok(());
+            }
+        }
+        None => return Ok(()),
+    }
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().eq_ignore_ascii_case("quit") {
+            break;
+        }
+        writeln!(writer, "{}", line)?;
+        match read_answer(&mut answers)? {
+            Some(answer) => output.write_all(answer.as_bytes())?,
+            None => break,
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+/// Reads the lines of one answer, None once the server closed the connection
+fn read_answer<R: BufRead>(answers: &mut R) -> io::Result<Option<String>> {
+    let mut answer = String::new();
+    loop {
+        let start = answer.len();
+        if answers.read_line(&mut answer)? == 0 {
+            return Ok(None);
+        }
+        if answer[start..].trim_end().is_empty() {
+            answer.truncate(start);
+            return Ok(Some(answer));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::BoardConfig;
+    use std::io::Cursor;
+
+    fn two_players() -> Board {
+        let mut board = Board::from_seed(BoardConfig::new(4, 3, 2).with_players(2), 1);
+        board.treasures[0].position.set((3, 0));
+        board.players[0].position.set((0, 0));
+        board.players[1].position.set((2, 2));
+        board
+    }
+
+    #[test]
+    fn answer_commands() {
+        let mut board = two_players();
+        assert_eq!(
+            answer(&mut board, 1, "SEARCH"),
+            "ERR it is the turn of player 1"
+        );
+        assert_eq!(
+            answer(&mut board, 0, "move (3,0)"),
+            "ERR You can't move that far! Movement is limited to 2 blocs"
+        );
+        assert!(answer(&mut board, 0, "MOVE [9,0]").starts_with("ERR Please respect"));
+        assert_eq!(answer(&mut board, 0, "MOVE (1,1)"), "OK moved to (1,1)");
+        assert_eq!(
+            answer(&mut board, 1, "SEARCH"),
+            "OK the treasure is 2 blocs away"
+        );
+        assert_eq!(
+            answer(&mut board, 0, "STATE"),
+            "OK player 1 to play, 1 treasures left\n..2.\n.1..\n...."
+        );
+        assert!(answer(&mut board, 0, "DIG").starts_with("ERR unknown command"));
+    }
+
+    #[test]
+    fn state_in_fog() {
+        let mut board = two_players();
+        board.config.visibility = Some(1);
+        assert_eq!(
+            answer(&mut board, 0, "STATE"),
+            "OK player 1 to play, 1 treasures left\n????\n..??\n1.??"
+        );
+        assert_eq!(
+            answer(&mut board, 1, "STATE"),
+            "OK player 1 to play, 1 treasures left\n?.2.\n?...\n????"
+        );
+    }
+
+    #[test]
+    fn answer_game_over() {
+        let mut board = two_players();
+        board.players[0].position.set((3, 0));
+        assert_eq!(
+            answer(&mut board, 0, "SEARCH"),
+            "OK you found the last treasure, player 1 wins"
+        );
+        assert_eq!(answer(&mut board, 0, "SEARCH"), "ERR the game is over");
+        assert!(answer(&mut board, 1, "STATE").starts_with("OK game over, player 1 wins\n"));
+    }
+
+    #[test]
+    fn serve_on_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener, two_players()));
+
+        // the first client keeps its player while the second one plays
+        let first = TcpStream::connect(addr).unwrap();
+        let mut greeting = String::new();
+        BufReader::new(&first).read_line(&mut greeting).unwrap();
+        assert_eq!(greeting, "OK you are player 1 of 2\n");
+
+        let mut output = Vec::new();
+        let input = Cursor::new("STATE\nSEARCH\nquit\nSTATE\n");
+        connect(addr, input, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output,
+            "OK you are player 2 of 2\n\
+             OK player 1 to play, 1 treasures left\n..2.\n....\n1...\n\
+             ERR it is the turn of player 1\n"
+        );
+    }
+
+    #[test]
+    fn serve_in_fog() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut board = two_players();
+        board.config.visibility = Some(1);
+        thread::spawn(move || serve(listener, board));
+
+        // the fully fogged top row does not end the answer early
+        let mut output = Vec::new();
+        connect(addr, Cursor::new("STATE\nSEARCH\n"), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "OK you are player 1 of 2\n\
+             OK player 1 to play, 1 treasures left\n????\n..??\n1.??\n\
+             OK the treasure is 3 blocs away\n"
+        );
+    }
+
+    #[test]
+    fn serve_full_game() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let board = Board::from_seed(BoardConfig::new(4, 3, 2), 1);
+        thread::spawn(move || serve(listener, board));
+
+        let first = TcpStream::connect(addr).unwrap();
+        BufReader::new(&first)
+            .read_line(&mut String::new())
+            .unwrap();
+        let mut output = Vec::new();
+        connect(addr, Cursor::new("STATE\n"), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "ERR the game is full\n");
+    }
+}
@@ -0,0 +1,319 @@
+use std::error::Error;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumString};
+
+use crate::utils::{Point, KNOWN_COLORS};
+
+/// Result of a `Board::search` on the player's square
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SearchOutcome {
+    /// The treasure was hidden on the searched square
+    Found,
+    /// The treasure is elsewhere, this many blocs away, with a hint depending on the `HintMode`
+    Distance(u16, Option<Hint>),
+}
+
+/// Extra information given by a failed search, on top of the distance
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum HintMode {
+    /// Only the distance
+    #[default]
+    None,
+    /// The direction of the treasure
+    Compass,
+    /// Whether the treasure is closer than at the previous search
+    HotCold,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Hint {
+    /// The treasure lies in this direction
+    Direction(Direction),
+    /// Closer to the treasure than at the previous search
+    Warmer,
+    /// Further from the treasure than at the previous search
+    Colder,
+    /// As far from the treasure as at the previous search
+    Same,
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Hint::Direction(direction) => write!(f, "to the {}", direction),
+            Hint::Warmer => write!(f, "warmer than your last search"),
+            Hint::Colder => write!(f, "colder than your last search"),
+            Hint::Same => write!(f, "as far as at your last search"),
+        }
+    }
+}
+
+/// Compass direction, north being towards the top of the board (increasing y)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Display, EnumString)]
+pub enum Direction {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Direction {
+    /// Direction of `to` seen from `from`, rounded to the closest of the eight directions
+    ///
+    /// Returns None when both points are the same.
+    pub fn between(from: &Point, to: &Point) -> Option<Direction> {
+        const ANTICLOCKWISE_FROM_EAST: [Direction; 8] = [
+            Direction::E,
+            Direction::NE,
+            Direction::N,
+            Direction::NW,
+            Direction::W,
+            Direction::SW,
+            Direction::S,
+            Direction::SE,
+        ];
+        if from == to {
+            return None;
+        }
+        let dx = to.x as f64 - from.x as f64;
+        let dy = to.y as f64 - from.y as f64;
+        let sector = (dy.atan2(dx).to_degrees() / 45.0).round() as i32;
+        Some(ANTICLOCKWISE_FROM_EAST[sector.rem_euclid(8) as usize])
+    }
+
+    /// Change of (x,y) of a single step in this direction
+    pub fn offset(&self) -> (i16, i16) {
+        match self {
+            Direction::N => (0, 1),
+            Direction::NE => (1, 1),
+            Direction::E => (1, 0),
+            Direction::SE => (1, -1),
+            Direction::S => (0, -1),
+            Direction::SW => (-1, -1),
+            Direction::W => (-1, 0),
+            Direction::NW => (-1, 1),
+        }
+    }
+}
+
+/// One of the two coordinates of a `Point`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+/// Reasons for `Board::validate_move_coordinates` and `Board::is_within_bounds` to reject a coordinate
+#[derive(Debug, Clone, PartialEq)]
+pub enum CoordError {
+    /// Nothing was entered
+    Empty,
+    /// The input is not of the form (x,y) or [x,y]
+    Format,
+    /// The input opens with `open` but does not end with the matching `close`
+    MismatchedBracket { open: char, close: char },
+    /// More than two coordinates were given
+    Dimensions { got: usize, expected: usize },
+    /// A coordinate could not be converted to a `u8`
+    Number(String),
+    /// A coordinate is past the edge of the board, `max` being the last valid value
+    OutOfBounds { axis: Axis, max: u8, got: u8 },
+}
+
+impl fmt::Display for CoordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoordError::Empty => write!(f, "Please enter a coordinate!"),
+            CoordError::Format => write!(f, "Incorrect format, please check your input!"),
+            CoordError::MismatchedBracket { open, close } => write!(
+                f,
+                "If you use '{}' for your coordinates, do not forget to end your input with '{}'!",
+                open, close
+            ),
+            CoordError::Dimensions { got, expected } => write!(
+                f,
+                "Wrong number of coordinates: {} coordinates provided instead of {}.",
+                got, expected
+            ),
+            CoordError::Number(n) => {
+                write!(f, "The number {} can not be converted to a coordinate", n)
+            }
+            CoordError::OutOfBounds { axis, max, got } => {
+                let (name, letter) = match axis {
+                    Axis::X => ("width", 'x'),
+                    Axis::Y => ("height", 'y'),
+                };
+                write!(
+                    f,
+                    "Please respect the map bounds!\nMax {} is {}, which is lower than your input of {}={}",
+                    name, max, letter, got
+                )
+            }
+        }
+    }
+}
+
+impl Error for CoordError {}
+
+/// Reasons for `Board::move_to` to refuse a move
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoveError {
+    /// The coordinates of a square could not be read or are past the edge of the board
+    Coord(CoordError),
+    /// A word of a route in compass steps is not a direction with an optional number of steps
+    Step(String),
+    /// The destination is further than `max` blocs away
+    TooFar { max: u8 },
+    /// The destination is a wall or water
+    Blocked,
+    /// The route is empty, the player would stay on their square
+    Stay,
+    /// Walking around the obstacles to the destination takes more than `max` blocs
+    NoPath { max: u8 },
+    /// The square `at` of a route is not a single step away from the square before it
+    NotAStep { at: Point },
+    /// A compass step of a route leads off the board
+    OffBoard,
+    /// The move costs more than the `left` stamina of the player
+    Exhausted { left: u16 },
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::Coord(err) => write!(f, "{}", err),
+            MoveError::Step(word) => write!(
+                f,
+                "\"{}\" is not a compass step, use a direction and a number of steps such as N3 or SE",
+                word
+            ),
+            MoveError::TooFar { max } => write!(
+                f,
+                "You can't move that far! Movement is limited to {} blocs",
+                max
+            ),
+            MoveError::Blocked => write!(f, "You can't stand on a wall or in the water!"),
+            MoveError::Stay => write!(f, "You are already there, pick another square!"),
+            MoveError::NoPath { max } => write!(
+                f,
+                "Walls and water are in the way, you can't get there in {} blocs",
+                max
+            ),
+            MoveError::NotAStep { at } => write!(
+                f,
+                "({},{}) is not a single step away from the square before it",
+                at.x, at.y
+            ),
+            MoveError::OffBoard => write!(f, "This route leads off the board!"),
+            MoveError::Exhausted { left } => write!(
+                f,
+                "You are too tired to walk that far, you only have {} stamina left",
+                left
+            ),
+        }
+    }
+}
+
+impl Error for MoveError {}
+
+impl From<CoordError> for MoveError {
+    fn from(err: CoordError) -> Self {
+        MoveError::Coord(err)
+    }
+}
+
+/// Reasons for `Board::undo` and `Board::redo` to leave the game as it is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UndoError {
+    /// Turns can only be taken back in practice games
+    NotPractice,
+    NothingToUndo,
+    NothingToRedo,
+}
+
+impl fmt::Display for UndoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UndoError::NotPractice => write!(
+                f,
+                "Turns can only be taken back in practice games, started with --practice!"
+            ),
+            UndoError::NothingToUndo => write!(f, "There is nothing to undo!"),
+            UndoError::NothingToRedo => write!(f, "There is nothing to redo!"),
+        }
+    }
+}
+
+impl Error for UndoError {}
+
+/// Reasons for `Player::set_color` to keep the colour of the player
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorError {
+    /// Nothing was entered
+    Empty,
+    /// A value of an RGB colour is above 255
+    OutOfRange,
+    /// The input is neither a known colour nor an RGB colour
+    Unknown(String),
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorError::Empty => write!(f, "Please enter a colour!"),
+            ColorError::OutOfRange => write!(f, "The value entered must be between 0 and 255!"),
+            ColorError::Unknown(color) => {
+                write!(
+                    f,
+                    "\"{}\" was not understood. The known colours are:",
+                    color
+                )?;
+                for c in &KNOWN_COLORS {
+                    write!(f, "\n   * {}", c)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for ColorError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        x,
+        y,
+        expected,
+        case(5, 9, Direction::N),
+        case(9, 9, Direction::NE),
+        case(9, 6, Direction::E),  // slightly north of east
+        case(9, 1, Direction::SE),
+        case(5, 0, Direction::S),
+        case(4, 0, Direction::S),  // slightly west of south
+        case(0, 0, Direction::SW),
+        case(0, 5, Direction::W),
+        case(1, 9, Direction::NW),
+        ::trace
+    )]
+    fn direction_tests(x: u8, y: u8, expected: Direction) {
+        let from = Point { x: 5, y: 5 };
+        assert_eq!(Direction::between(&from, &Point { x, y }), Some(expected));
+    }
+
+    #[test]
+    fn direction_same_point() {
+        let p = Point { x: 3, y: 3 };
+        assert_eq!(Direction::between(&p, &p), None);
+    }
+}
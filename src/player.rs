@@ -1,27 +1,33 @@
-#![allow(dead_code)]
-
+use rand::distributions::Distribution;
 use rand::Rng;
 
-use termcolor::Color;
-#[path = "./utils.rs"]
-mod utils;
-use crate::utils::check_color;
+use crate::board::BoardConfig;
+use crate::outcome::ColorError;
+use crate::utils::{check_color, Point};
 use regex::Regex;
-use utils::Point;
+use termcolor::Color;
 
 #[derive(Debug)]
 pub struct Player {
-    pub(crate) position: Point,
-    pub(crate) color: Color,
+    pub position: Point,
+    pub color: Color,
+    /// Where the player spawned, used to score the game
+    pub start: Point,
 }
 
 impl Player {
-    pub fn new() -> Self {
-        let mut rng = rand::thread_rng();
-        let rand_point: Point = rng.gen();
+    /// Creates a player at a random position on a board of the given dimensions
+    pub fn new(config: &BoardConfig) -> Self {
+        Self::with_rng(config, &mut rand::thread_rng())
+    }
+
+    /// Creates a player at a position drawn from `rng`
+    pub fn with_rng<R: Rng + ?Sized>(config: &BoardConfig, rng: &mut R) -> Self {
+        let rand_point: Point = config.sample(rng);
         Self {
             position: rand_point,
             color: Color::Green,
+            start: rand_point,
         }
     }
 
@@ -31,14 +37,15 @@ impl Player {
     /// Tries to convert the given str to a known colour or to a RGB tuple
     ///
     /// Known colours:
-    ///    - "Black", "Blue", "Green", "Red", "Cyan", "Magenta", "Yellow", "White"
+    ///  - "Black", "Blue", "Green", "Red", "Cyan", "Magenta", "Yellow", "White"
+    ///
     /// RGB Tuple:
-    ///    - (u8,u8,u8)
+    ///  - (u8,u8,u8)
     ///
-    /// returns true on success, false on failure
-    pub fn set_color(&mut self, color: &str) -> bool {
-        if color == "" {
-            return false;
+    /// The colour of the player is left as it is on failure
+    pub fn set_color(&mut self, color: &str) -> Result<(), ColorError> {
+        if color.is_empty() {
+            return Err(ColorError::Empty);
         }
         let s: String = color.chars().filter(|c| !c.is_whitespace()).collect();
         const RGB_REGEX: &str = r#"^\d{1,3},\d{1,3},\d{1,3}$"#;
@@ -49,10 +56,7 @@ impl Player {
             for i in &split {
                 match i.parse::<u8>() {
                     Ok(_) => {}
-                    Err(_) => {
-                        println! {"The value entered must be between 0 and 255!"};
-                        return false;
-                    }
+                    Err(_) => return Err(ColorError::OutOfRange),
                 };
             }
 
@@ -61,15 +65,15 @@ impl Player {
                 split[1].parse::<u8>().unwrap(),
                 split[2].parse::<u8>().unwrap(),
             );
-            return true;
+            return Ok(());
         }
 
         if check_color(s.as_str()) {
             self.color = s.parse().unwrap();
-            return true;
+            return Ok(());
         }
 
-        return false;
+        Err(ColorError::Unknown(s))
     }
 }
 
@@ -92,7 +96,20 @@ mod test {
     ::trace
     )]
     fn player_color_tests(input: &str, expected: bool) {
-        let mut player = Player::new();
-        assert_eq!(player.set_color(input), expected);
+        let mut player = Player::new(&BoardConfig::default());
+        assert_eq!(player.set_color(input).is_ok(), expected);
+    }
+
+    #[test]
+    fn player_color_errors() {
+        let mut player = Player::new(&BoardConfig::default());
+        assert_eq!(player.set_color(""), Err(ColorError::Empty));
+        assert_eq!(player.set_color("0,256,0"), Err(ColorError::OutOfRange));
+        assert_eq!(
+            player.set_color("Cy am"),
+            Err(ColorError::Unknown("Cyam".to_string()))
+        );
+        // a failed attempt keeps the previous colour
+        assert_eq!(player.color, Color::Green);
     }
 }
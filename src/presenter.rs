@@ -0,0 +1,525 @@
+//! Terminal rendering of the game
+//!
+//! The `Board` only returns structured results, this module turns them into coloured text on
+//! the terminal.
+
+use std::fmt::Display;
+use std::io::{self, Write};
+
+use termcolor::{BufferWriter, Color, ColorChoice, ColorSpec, WriteColor};
+
+use crate::bench::Stats;
+use crate::board::{Board, BoardConfig, Difficulty};
+use crate::command::Topic;
+use crate::history::{Score, Turn};
+use crate::outcome::{HintMode, SearchOutcome};
+use crate::scores::{Category, ScoreEntry};
+
+const BOARD_COLOR: Color = Color::White;
+
+pub fn print_rules(config: &BoardConfig) -> io::Result<()> {
+    let buffer_writer = BufferWriter::stderr(ColorChoice::Always);
+    let mut buffer = buffer_writer.buffer();
+    const WHITE: Option<Color> = Some(Color::White);
+    const HL: Option<Color> = Some(Color::Green);
+
+    buffer.set_color(ColorSpec::new().set_fg(WHITE))?;
+    writeln!(&mut buffer, "Welcome to the Treasure Hunt!\n")?;
+    write!(&mut buffer, "You can ")?;
+    buffer.set_color(ColorSpec::new().set_fg(HL))?;
+    write!(&mut buffer, "Move")?;
+    buffer.set_color(ColorSpec::new().set_fg(WHITE))?;
+    write!(&mut buffer, " around the place to ")?;
+
+    buffer.set_color(ColorSpec::new().set_fg(HL))?;
+    write!(&mut buffer, "Search")?;
+    buffer.set_color(ColorSpec::new().set_fg(WHITE))?;
+    writeln!(&mut buffer, " for the Treasure! Good Luck...")?;
+
+    writeln!(&mut buffer, "\t[*] Search will take one action, it lets you search for the Treasure on your current coordinates.")?;
+    writeln!(&mut buffer, "\t[*] \"Move (x,y)\" or \"Move [x,y]\" to go to a coordinate, you walk there one step at a time and leave a trail of '{}'.\n\t[*] You can only move within the board and you can only Move {} blocs away at most.",Board::ROUTE_CHAR, config.max_dist)?;
+    writeln!(&mut buffer, "You are represented by the character '{}' on the map, an '{}' signifies you have searched the area, and a '#' is a wall.\n",Board::PLAYER_CHAR, Board::SEARCHED_CHAR)?;
+    if config.obstacles > 0 {
+        writeln!(&mut buffer, "Walls '{}' and water '{}' are scattered on the board: you can not stand on them, moves go around them.\n", Board::WALL_CHAR, Board::WATER_CHAR)?;
+    }
+    if let Some(radius) = config.visibility {
+        writeln!(&mut buffer, "The fog of war hides the squares more than {} blocs away from you, except the ones already searched: remember where you have been!\n", radius)?;
+    }
+    if let Some(stamina) = config.stamina {
+        writeln!(&mut buffer, "You have {} stamina: every bloc walked costs 1 and every search costs {}. Once you can not afford a search the game is lost!\n", stamina, Board::SEARCH_COST)?;
+    }
+    if let Some(every) = config.drift {
+        writeln!(&mut buffer, "The treasures are on the move: every {} turns they drift one square away, never onto a searched square. Do not trust old searches for too long!\n", every)?;
+    }
+    if config.practice {
+        writeln!(&mut buffer, "This is a practice game: \"undo\" takes back a turn and \"redo\" plays it again, the game is not scored.\n")?;
+    }
+
+    buffer_writer.print(&buffer)
+}
+
+/// Lists the difficulties the game can be played at, as they would change `config`
+pub fn print_difficulties(config: &BoardConfig) {
+    println!("[*] Difficulty");
+    for (i, &difficulty) in Difficulty::ALL.iter().enumerate() {
+        let preset = config.with_difficulty(difficulty);
+        let hints = match preset.hints {
+            HintMode::None => String::from("no hints"),
+            hints => format!("{} hints", hints),
+        };
+        let stamina = match preset.stamina {
+            Some(stamina) => format!("{} stamina", stamina),
+            None => String::from("unlimited stamina"),
+        };
+        println!(
+            "\t{}. {:<7} {}x{}, range {}, {}, {}, {} treasure(s), {}",
+            i + 1,
+            difficulty,
+            preset.width,
+            preset.height,
+            preset.max_dist,
+            preset.metric,
+            hints,
+            preset.treasures,
+            stamina
+        );
+    }
+}
+
+/// Print the help on `topic`, or on every command
+pub fn print_help(config: &BoardConfig, topic: Option<Topic>) {
+    let shown = |t: Topic| topic.is_none() || topic == Some(t);
+    println!();
+    if shown(Topic::Search) {
+        println!("[*] Search command: \"search\" or \"s\"");
+        println!(
+            "This will tell you how far away you are from the nearest treasure.\n\
+        It will also mark the searched square, a found treasure is shown as '{}'.\n\
+        Once you have searched every treasure square, you win the game.",
+            Board::TREASURE_CHAR
+        );
+        if config.stamina.is_some() {
+            println!(
+                "\t[*] Each search costs {} stamina, and each bloc walked 1.",
+                Board::SEARCH_COST
+            );
+        }
+        if let Some(every) = config.drift {
+            println!(
+                "\t[*] The hidden treasures drift one square every {} turns, the distances change with them.",
+                every
+            );
+        }
+        println!();
+    }
+
+    if shown(Topic::Move) {
+        println!("[*] Move command: \"move (x,y)\" or \"m x y\"");
+        println!(
+            "To move to a coordinate, please use one of the following formats:\
+                        \n\t1. [x,y]: x ϵ [0;{0}), y ϵ [0;{1})\
+                        \n\t2. (x,y): x ϵ [0;{0}), y ϵ [0;{1})\
+                        \n\t3. x y: x ϵ [0;{0}), y ϵ [0;{1})\
+                        \n\t4. N3 E1: steps in the compass directions N, S, E, W, NE, NW, SE and SW\
+                        \n\t5. (1,1) (2,1) (3,2): every square of the route, one step apart\
+                        \n\t[*] A single destination is reached by the shortest walk.\
+                        \n\t[*] To use hex numbers, prefix them with '0x'.\
+                        \n\t[*] Without coordinates, you will be asked for them.\
+                        \n\t[*] You can move up to {2} blocs away, distances are measured with the {3} metric.",
+            config.width, config.height, config.max_dist, config.metric
+        );
+        if config.obstacles > 0 {
+            println!(
+                "\t[*] Walls '{}' and water '{}' block the way, the walk around them must fit in {} blocs too.",
+                Board::WALL_CHAR,
+                Board::WATER_CHAR,
+                config.max_dist
+            );
+        }
+        println!();
+    }
+
+    if shown(Topic::Help) {
+        println!("[*] Help command: \"help [command]\" or \"h [command]\"");
+        println!("Shows this help, or only the help of the command.");
+        println!();
+    }
+
+    if shown(Topic::Save) || shown(Topic::Load) {
+        println!("[*] Save and Load commands: \"save <path>\" and \"load <path>\"");
+        println!("Writes the game to a file, or replaces the game with the one of the file.");
+        println!();
+    }
+
+    if shown(Topic::Scores) {
+        println!("[*] Scores command");
+        println!("Shows the best games played on boards like this one.");
+        println!();
+    }
+
+    if shown(Topic::Undo) || shown(Topic::Redo) {
+        println!("[*] Undo and Redo commands: \"undo\" or \"u\", \"redo\" or \"r\"");
+        println!(
+            "Takes back the last move or search, or plays again the last one taken back.\n\
+        Only in practice games, started with --practice, which do not make it to the high scores."
+        );
+        println!();
+    }
+
+    if shown(Topic::Quit) {
+        println!("[*] Quit command: \"quit\" or \"q\"");
+        println!("Leave the program.");
+        println!();
+    }
+}
+
+/// Prints the result of a search, `remaining` being the number of treasures still hidden
+///
+/// The Treasure is X blocs away. (with highlight on X)
+pub fn print_search_outcome(outcome: SearchOutcome, remaining: usize) -> io::Result<()> {
+    let (distance, hint) = match outcome {
+        SearchOutcome::Found if remaining == 0 => {
+            println!("Congratulation you found the treasure!");
+            return Ok(());
+        }
+        SearchOutcome::Found => {
+            println!(
+                "You found a treasure! {} more to find, keep searching.",
+                remaining
+            );
+            return Ok(());
+        }
+        SearchOutcome::Distance(distance, hint) => (distance, hint),
+    };
+    let buffer_writer = BufferWriter::stdout(ColorChoice::Always);
+    let mut buffer = buffer_writer.buffer();
+    buffer.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+    write!(&mut buffer, "The treasure is ")?;
+    buffer.set_color(ColorSpec::new().set_fg(Some(Color::Rgb(0, 102, 255))))?;
+    write!(&mut buffer, "{}", distance)?;
+    buffer.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+    write!(&mut buffer, " blocs away")?;
+    if let Some(hint) = hint {
+        write!(&mut buffer, ", {}", hint)?;
+    }
+    writeln!(&mut buffer, ".")?;
+    buffer_writer.print(&buffer)
+}
+
+/// Prints an error returned by the `Board` so the user can correct their input
+pub fn print_error<E: Display>(err: &E) {
+    println!("{}", err);
+}
+
+/// Announces whose turn it is in a multiplayer game, in the colour of that player
+pub fn print_turn(board: &Board) -> io::Result<()> {
+    let buffer_writer = BufferWriter::stdout(ColorChoice::Always);
+    let mut buffer = buffer_writer.buffer();
+    buffer.set_color(ColorSpec::new().set_fg(Some(board.player().color)))?;
+    writeln!(
+        &mut buffer,
+        "Player {}, your turn.",
+        board.current_player() + 1
+    )?;
+    buffer.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+    buffer_writer.print(&buffer)
+}
+
+/// Prints what the computer player did during its turn
+pub fn print_bot_turn(turn: &Turn) {
+    let player = turn.player() + 1;
+    match turn {
+        Turn::Move { to, .. } => {
+            println!("Player {} (computer) moves to ({},{}).", player, to.x, to.y)
+        }
+        Turn::Search {
+            at,
+            outcome: SearchOutcome::Found,
+            ..
+        } => println!(
+            "Player {} (computer) found a treasure at ({},{})!",
+            player, at.x, at.y
+        ),
+        Turn::Search {
+            at,
+            outcome: SearchOutcome::Distance(distance, _),
+            ..
+        } => println!(
+            "Player {} (computer) searched ({},{}), the treasure is {} blocs away.",
+            player, at.x, at.y, distance
+        ),
+    }
+}
+
+/// Prints the turn taken back by an undo, or played again by a redo
+pub fn print_undo(board: &Board, turn: &Turn, redo: bool) {
+    let action = match turn {
+        Turn::Move { to, .. } => format!("the move to ({},{})", to.x, to.y),
+        Turn::Search { at, .. } => format!("the search at ({},{})", at.x, at.y),
+    };
+    let player = match board.players.len() {
+        1 => String::new(),
+        _ => format!(" of player {}", turn.player() + 1),
+    };
+    let verb = if redo { "Played again" } else { "Took back" };
+    println!("{} {}{}.", verb, action, player);
+}
+
+/// Prints the winner of a multiplayer game and the score of every player
+pub fn print_results(board: &Board) {
+    if let Some(winner) = board.winner() {
+        println!("Player {} wins!", winner + 1);
+    }
+    for player in 0..board.players.len() {
+        println!("Player {}", player + 1);
+        print_score(&board.score(player));
+    }
+}
+
+/// Tells the players they ran out of stamina and where the treasures left were hidden
+pub fn print_defeat(board: &Board) {
+    println!("[*] Out of stamina, the game is lost!");
+    for treasure in board.treasures().iter().filter(|t| !t.found) {
+        println!(
+            "\tA treasure was hidden at ({},{}).",
+            treasure.position.x, treasure.position.y
+        );
+    }
+    println!();
+}
+
+/// Prints the summary shown at the end of a game
+pub fn print_score(score: &Score) {
+    println!("[*] Score");
+    println!("\tTurns taken:     {}", score.turns);
+    println!("\tSearches:        {}", score.searches);
+    println!("\tDistance walked: {} blocs", score.distance_walked);
+    println!(
+        "\tBest possible:   {} turns (knowing where the treasure is)",
+        score.optimal_turns
+    );
+    println!();
+}
+
+/// Prints the columns of the benchmark table
+pub fn print_bench_header(games: u32) {
+    println!("[*] Turns to find every treasure, over {} games", games);
+    println!(
+        "\t{:<7} {:<9} {:<13} {:>8} {:>6} {:>6} {:>10}",
+        "Board", "Metric", "Strategy", "Mean", "Median", "p95", "Unfinished"
+    );
+}
+
+/// Prints the results of a strategy on boards of `config`
+pub fn print_bench_row(config: &BoardConfig, strategy: &str, stats: &Stats) {
+    println!(
+        "\t{:<7} {:<9} {:<13} {:>8.1} {:>6} {:>6} {:>10}",
+        format!("{}x{}", config.width, config.height),
+        config.metric.to_string(),
+        strategy,
+        stats.mean,
+        stats.median,
+        stats.p95,
+        stats.unfinished
+    );
+}
+
+/// Prints the best games of a category of the leaderboard
+pub fn print_leaderboard(category: &Category, entries: &[&ScoreEntry]) {
+    println!(
+        "[*] High scores on {}x{} boards ({})",
+        category.width, category.height, category.difficulty
+    );
+    if entries.is_empty() {
+        println!("\tNo game won yet, be the first!");
+    }
+    for (rank, entry) in entries.iter().enumerate() {
+        println!(
+            "\t{:>2}. {:<16} {:>4} turns {:>4} searches   {}",
+            rank + 1,
+            entry.name,
+            entry.turns,
+            entry.searches,
+            entry.date
+        );
+    }
+    println!();
+}
+
+/// Colour of a searched square, the one of the last player who searched it in a multiplayer game
+fn searched_color(board: &Board, x: u8, y: u8) -> Color {
+    if board.players.len() == 1 {
+        return Color::Cyan;
+    }
+    board
+        .turns()
+        .iter()
+        .rev()
+        .find_map(|turn| match turn {
+            Turn::Search { at, player, .. } if at.as_tuple() == (x, y) => {
+                Some(board.players[*player].color)
+            }
+            _ => None,
+        })
+        .unwrap_or(Color::Cyan)
+}
+
+/// Character and colour of the square (x,y) of the board
+///
+/// Squares within reach of the current player are highlighted, the closer the brighter. Squares
+/// the current player can not see through the fog of war are blank.
+pub(crate) fn square(board: &Board, x: u8, y: u8) -> (char, Color) {
+    if !board.is_visible(board.current_player(), x, y) {
+        return (Board::FOG_CHAR, BOARD_COLOR);
+    }
+    let config = board.config();
+    let mut grid_c = board.board[x as usize][y as usize];
+    match grid_c {
+        Board::WALL_CHAR => return (grid_c, BOARD_COLOR),
+        Board::WATER_CHAR => return (grid_c, Color::Blue),
+        _ => {}
+    }
+    let mut color = BOARD_COLOR;
+    let dist = board.get_distance_to(x, y);
+    let searched = (grid_c == Board::SEARCHED_CHAR).then(|| searched_color(board, x, y));
+    if let Some(searched) = searched {
+        color = searched;
+    }
+    // depending on your terminal you will not see much difference
+    if dist <= config.max_dist as u16 {
+        color = Color::Rgb(102, 255, 255);
+    }
+    if dist <= config.max_dist as u16 / 2 {
+        color = Color::Rgb(0, 255, 255);
+    }
+    if dist == 1 {
+        color = Color::Yellow;
+    }
+    // in a multiplayer game the owner of a searched square matters more than the reach
+    if let (Some(searched), true) = (searched, board.players.len() > 1) {
+        color = searched;
+    }
+    if grid_c == Board::EMPTY_CHAR {
+        if let Some(Turn::Move { route, player, .. }) = board
+            .turns()
+            .iter()
+            .rev()
+            .find(|turn| matches!(turn, Turn::Move { .. }))
+        {
+            if route.iter().any(|p| p.as_tuple() == (x, y)) {
+                color = board.players[*player].color;
+                grid_c = Board::ROUTE_CHAR;
+            }
+        }
+    }
+    // the treasures left are revealed once the game is lost
+    if let Some(treasure) = board
+        .treasures()
+        .iter()
+        .find(|t| (t.found || board.is_lost()) && t.position.as_tuple() == (x, y))
+    {
+        color = if treasure.found {
+            Color::Yellow
+        } else {
+            Color::Red
+        };
+        grid_c = Board::TREASURE_CHAR;
+    }
+    // the current player wins a shared square so that it stays visible
+    let current = board.current_player();
+    let others = (0..board.players.len()).filter(|&i| i != current);
+    if let Some(player) = std::iter::once(current)
+        .chain(others)
+        .map(|i| &board.players[i])
+        .find(|p| p.position.as_tuple() == (x, y))
+    {
+        color = player.color;
+        grid_c = Board::PLAYER_CHAR;
+    }
+    (grid_c, color)
+}
+
+/// Prints the `Board` to `stdout`.
+///
+/// When the function returns, the terminal color is `White`.
+/// The grid follows the dimensions of the `BoardConfig` the board was created with.
+pub fn print_board(board: &Board) -> io::Result<()> {
+    let config = board.config();
+    let buffer_writer = BufferWriter::stdout(ColorChoice::Always);
+    let mut buffer = buffer_writer.buffer();
+
+    // Top row
+    buffer.set_color(ColorSpec::new().set_fg(Some(BOARD_COLOR)))?;
+    write!(&mut buffer, "{:>4}", "#")?;
+    for _ in 0..config.width {
+        write!(&mut buffer, "###")?;
+    }
+    writeln!(&mut buffer, "#")?;
+
+    // Main grid
+    for y in (0..config.height).rev() {
+        write!(&mut buffer, "{:>2} #", y)?; // Side coordinates
+
+        for x in 0..config.width {
+            let (grid_c, color) = square(board, x, y);
+            buffer.set_color(ColorSpec::new().set_fg(Some(color)))?;
+            write!(&mut buffer, "{:^3}", grid_c)?;
+            buffer.set_color(ColorSpec::new().set_fg(Some(BOARD_COLOR)))?;
+        }
+
+        writeln!(&mut buffer, "#")?; // Side column
+    }
+
+    // Bottom row
+    write!(&mut buffer, "{:>4}", "#")?;
+    for _ in 0..config.width {
+        write!(&mut buffer, "###")?;
+    }
+    writeln!(&mut buffer, "#")?;
+
+    // Bottom coordinates
+    write!(&mut buffer, "{:4}", "")?;
+    for x in 0..config.width {
+        write!(&mut buffer, "{:^3}", x)?;
+    }
+    writeln!(&mut buffer)?;
+
+    // Status
+    if let (Some(stamina), Some(left)) =
+        (config.stamina, board.stamina_left(board.current_player()))
+    {
+        buffer.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+        writeln!(
+            &mut buffer,
+            "Stamina: {}/{}, a search costs {}",
+            left,
+            stamina,
+            Board::SEARCH_COST
+        )?;
+    }
+
+    writeln!(&mut buffer)?;
+    buffer.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+    buffer_writer.print(&buffer)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn searched_square_keeps_its_owner_color() {
+        let mut board = Board::from_seed(BoardConfig::new(6, 6, 2).with_players(2), 1);
+        board.treasures[0].position.set((5, 5));
+        board.players[0].position.set((1, 1));
+        board.players[1].position.set((1, 2));
+        board.search();
+        board.players[0].position.set((0, 0));
+        // the square is next to the second player, it would be highlighted otherwise
+        assert_eq!(
+            square(&board, 1, 1),
+            (Board::SEARCHED_CHAR, board.players[0].color)
+        );
+        assert_ne!(board.players[0].color, Color::Yellow);
+    }
+}
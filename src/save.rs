@@ -0,0 +1,526 @@
+//! Saving and loading a game in progress
+//!
+//! A `Board` is written as a JSON document tagged with `SAVE_VERSION`, so that files written by
+//! an older version of the game can be detected instead of being misread.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use termcolor::Color;
+
+use crate::board::{Board, BoardConfig, Drift, Treasure};
+use crate::history::Turn;
+use crate::player::Player;
+use crate::terrain;
+use crate::utils::Point;
+
+/// Version of the on-disk format, bump it whenever `SaveFile` changes
+///
+/// Older versions are still read as long as the new fields have a default.
+pub const SAVE_VERSION: u32 = 12;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SaveFile {
+    version: u32,
+    config: BoardConfig,
+    /// One string per row, `grid[y]` holds the squares (0,y) to (width-1,y)
+    grid: Vec<String>,
+    /// Since version 6, older files hold a single `player` and its `start`
+    players: Vec<SavedPlayer>,
+    /// Index of the player whose turn it is, since version 6
+    current: usize,
+    /// Since version 5, older files hold a single `treasure` and `treasure_found` flag
+    treasures: Vec<Treasure>,
+    /// Since version 2, searches carry a hint since version 4
+    #[serde(default)]
+    turns: Vec<Turn>,
+    /// Since version 12, along with `drifts`
+    #[serde(default)]
+    drift_seed: u64,
+    #[serde(default)]
+    drifts: Vec<Drift>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedPlayer {
+    position: Point,
+    /// Same format as `Player::set_color`: a colour name or "r,g,b"
+    color: String,
+    /// Missing in files older than version 2, where the current position is used instead
+    #[serde(default)]
+    start: Option<Point>,
+}
+
+/// Reasons for a save or a load to fail
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The file was written with an unknown version of the format, the versions up to
+    /// `expected` are upgraded when loaded
+    Version {
+        found: u32,
+        expected: u32,
+    },
+    /// The file is well formed but does not describe a valid game
+    Invalid(String),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "Could not access the save file: {}", err),
+            SaveError::Json(err) => write!(f, "The save file is corrupted: {}", err),
+            SaveError::Version { found, expected } => write!(
+                f,
+                "The save file uses version {} of the format, versions 1 to {} are supported",
+                found, expected
+            ),
+            SaveError::Invalid(reason) => write!(f, "The save file is invalid: {}", reason),
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(err: serde_json::Error) -> Self {
+        SaveError::Json(err)
+    }
+}
+
+impl Board {
+    /// Writes the game to `path`, overwriting the file if it exists
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SaveError> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Reads a game previously written by `Board::save`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Board, SaveError> {
+        Board::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Serializes the game to the save file format
+    pub fn to_json(&self) -> Result<String, SaveError> {
+        let grid = (0..self.config.height as usize)
+            .map(|y| self.board.iter().map(|column| column[y]).collect())
+            .collect();
+        let save = SaveFile {
+            version: SAVE_VERSION,
+            config: self.config,
+            grid,
+            players: self
+                .players
+                .iter()
+                .map(|player| {
+                    Ok(SavedPlayer {
+                        position: player.position,
+                        color: color_to_string(player.color)?,
+                        start: Some(player.start),
+                    })
+                })
+                .collect::<Result<_, SaveError>>()?,
+            current: self.current,
+            treasures: self.treasures.clone(),
+            turns: self.turns.clone(),
+            drift_seed: self.drift_seed,
+            drifts: self.drifts.clone(),
+        };
+        Ok(serde_json::to_string_pretty(&save)?)
+    }
+
+    /// Rebuilds a game from the save file format
+    pub fn from_json(json: &str) -> Result<Board, SaveError> {
+        let mut file: serde_json::Value = serde_json::from_str(json)?;
+        let version = file["version"].as_u64().unwrap_or(0) as u32;
+        if version == 0 || version > SAVE_VERSION {
+            return Err(SaveError::Version {
+                found: version,
+                expected: SAVE_VERSION,
+            });
+        }
+        // the searches of older turn logs were not given a hint
+        if version < 4 {
+            if let Some(turns) = file.get_mut("turns").and_then(|t| t.as_array_mut()) {
+                let distances = turns.iter_mut().filter_map(|turn| {
+                    turn.get_mut("Search")?
+                        .get_mut("outcome")?
+                        .get_mut("Distance")
+                });
+                for distance in distances {
+                    *distance = serde_json::json!([distance.take(), null]);
+                }
+            }
+        }
+        if version < 5 {
+            let treasure = Treasure {
+                position: serde_json::from_value(file["treasure"].take())?,
+                found: serde_json::from_value(file["treasure_found"].take())?,
+            };
+            file["treasures"] = serde_json::to_value([treasure])?;
+        }
+        if version < 6 {
+            let mut player = file["player"].take();
+            player["start"] = file["start"].take();
+            file["players"] = serde_json::Value::Array(vec![player]);
+            file["current"] = 0.into();
+        }
+        let save: SaveFile = serde_json::from_value(file)?;
+
+        let config = save.config;
+        if config.width == 0 || config.height == 0 {
+            return Err(SaveError::Invalid("the board is empty".to_string()));
+        }
+        if config.max_dist == 0 {
+            return Err(SaveError::Invalid("the players can not move".to_string()));
+        }
+        if save.grid.len() != config.height as usize
+            || save
+                .grid
+                .iter()
+                .any(|row| row.chars().count() != config.width as usize)
+        {
+            return Err(SaveError::Invalid(format!(
+                "the grid is not {}x{}",
+                config.width, config.height
+            )));
+        }
+
+        let mut grid = vec![vec![Board::EMPTY_CHAR; config.height as usize]; config.width as usize];
+        for (y, row) in save.grid.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                grid[x][y] = c;
+            }
+        }
+        if save.treasures.is_empty() {
+            return Err(SaveError::Invalid("there is no treasure".to_string()));
+        }
+        if save.players.is_empty() || save.current >= save.players.len() {
+            return Err(SaveError::Invalid("the players are missing".to_string()));
+        }
+        let mut players = Vec::new();
+        for saved in &save.players {
+            let mut player = Player {
+                position: saved.position,
+                color: Color::White,
+                start: saved.start.unwrap_or(saved.position),
+            };
+            if player.set_color(&saved.color).is_err() {
+                return Err(SaveError::Invalid(format!(
+                    "unknown colour \"{}\"",
+                    saved.color
+                )));
+            }
+            players.push(player);
+        }
+        let treasures = save.treasures.iter().map(|t| ("treasure", t.position));
+        let positions = players
+            .iter()
+            .flat_map(|p| vec![("player", p.position), ("start", p.start)]);
+        for (name, p) in positions.chain(treasures) {
+            if p.x >= config.width || p.y >= config.height {
+                return Err(SaveError::Invalid(format!(
+                    "the {} is outside of the board",
+                    name
+                )));
+            }
+            if !terrain::is_passable(grid[p.x as usize][p.y as usize]) {
+                return Err(SaveError::Invalid(format!(
+                    "the {} is in a wall or in the water",
+                    name
+                )));
+            }
+        }
+        if save
+            .drifts
+            .iter()
+            .any(|d| d.treasure >= save.treasures.len())
+        {
+            return Err(SaveError::Invalid(
+                "an unknown treasure drifted".to_string(),
+            ));
+        }
+        if save.turns.iter().any(|turn| turn.player() >= players.len()) {
+            return Err(SaveError::Invalid(
+                "a turn was played by an unknown player".to_string(),
+            ));
+        }
+        // `undo` goes back to the squares of the turns and the drifts
+        let mut visited = save.turns.iter().flat_map(|turn| match turn {
+            Turn::Move {
+                from, to, route, ..
+            } => vec![*from, *to].into_iter().chain(route.clone()).collect(),
+            Turn::Search { at, .. } => vec![*at],
+        });
+        let mut drifted = save.drifts.iter().flat_map(|d| vec![d.from, d.to]);
+        let outside = |p: Point| p.x >= config.width || p.y >= config.height;
+        if visited.any(outside) {
+            return Err(SaveError::Invalid(
+                "a turn was played outside of the board".to_string(),
+            ));
+        }
+        if drifted.any(outside) {
+            return Err(SaveError::Invalid(
+                "a treasure drifted outside of the board".to_string(),
+            ));
+        }
+        let board = Board {
+            board: grid,
+            players,
+            current: save.current,
+            config,
+            treasures: save.treasures,
+            turns: save.turns,
+            undone: Vec::new(),
+            drift_seed: save.drift_seed,
+            drifts: save.drifts,
+        };
+        Ok(board)
+    }
+}
+
+/// Inverse of `Player::set_color`
+fn color_to_string(color: Color) -> Result<String, SaveError> {
+    let name = match color {
+        Color::Black => "Black",
+        Color::Blue => "Blue",
+        Color::Green => "Green",
+        Color::Red => "Red",
+        Color::Cyan => "Cyan",
+        Color::Magenta => "Magenta",
+        Color::Yellow => "Yellow",
+        Color::White => "White",
+        Color::Rgb(r, g, b) => return Ok(format!("{},{},{}", r, g, b)),
+        other => {
+            return Err(SaveError::Invalid(format!(
+                "the colour {:?} can not be saved",
+                other
+            )))
+        }
+    };
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::outcome::SearchOutcome;
+    use rstest::rstest;
+
+    #[rstest(
+        color,
+        case(Color::Green),
+        case(Color::Magenta),
+        case(Color::Rgb(133, 230, 89)),
+        ::trace
+    )]
+    fn save_round_trip(color: Color) {
+        let mut board = Board::from_seed(BoardConfig::new(8, 12, 3), 5);
+        board.players[0].color = color;
+        board.search();
+        board.board[7][11] = Board::SEARCHED_CHAR;
+
+        let loaded = Board::from_json(&board.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.board, board.board);
+        assert_eq!(loaded.config, board.config);
+        assert_eq!(loaded.players[0].position, board.players[0].position);
+        assert_eq!(loaded.players[0].color, board.players[0].color);
+        assert_eq!(loaded.players[0].start, board.players[0].start);
+        assert_eq!(loaded.treasures, board.treasures);
+        assert_eq!(loaded.turns(), board.turns());
+    }
+
+    #[test]
+    fn save_round_trip_players() {
+        let config = BoardConfig::new(8, 12, 3).with_players(3);
+        let mut board = Board::from_seed(config, 5);
+        board.players[2].color = Color::Yellow;
+        board.search();
+        board.search();
+
+        let loaded = Board::from_json(&board.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.players.len(), 3);
+        assert_eq!(loaded.current_player(), 2);
+        assert_eq!(loaded.players[2].color, Color::Yellow);
+        assert_eq!(loaded.players[1].position, board.players[1].position);
+        assert_eq!(loaded.turns(), board.turns());
+    }
+
+    #[test]
+    fn save_round_trip_drift() {
+        let config = BoardConfig::new(8, 12, 3).with_drift(Some(1));
+        let mut board = Board::from_seed(config, 5);
+        board.search();
+
+        let mut loaded = Board::from_json(&board.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.drifts, board.drifts);
+        assert_eq!(loaded.treasures, board.treasures);
+        // the treasures keep drifting the same way
+        board.search();
+        loaded.search();
+        assert_eq!(loaded.treasures, board.treasures);
+    }
+
+    /// Save file of a single player and single treasure `board` as written before version 5
+    fn legacy_json(board: &Board, version: u32) -> serde_json::Value {
+        let mut json: serde_json::Value = serde_json::from_str(&board.to_json().unwrap()).unwrap();
+        let file = json.as_object_mut().unwrap();
+        file.insert("version".to_string(), version.into());
+        file.remove("current");
+        let mut player = file.remove("players").unwrap()[0].take();
+        file.insert("start".to_string(), player["start"].take());
+        player.as_object_mut().unwrap().remove("start");
+        file.insert("player".to_string(), player);
+        file.remove("treasures");
+        let treasure = board.treasures[0];
+        file.insert(
+            "treasure".to_string(),
+            serde_json::to_value(treasure.position).unwrap(),
+        );
+        file.insert("treasure_found".to_string(), treasure.found.into());
+        json
+    }
+
+    #[test]
+    fn load_version_1() {
+        let board = Board::from_seed(BoardConfig::default(), 1);
+        let mut json = legacy_json(&board, 1);
+        let file = json.as_object_mut().unwrap();
+        file.remove("start");
+        file.remove("turns");
+
+        let loaded = Board::from_json(&json.to_string()).unwrap();
+        assert_eq!(loaded.players[0].start, board.players[0].position);
+        assert!(loaded.turns().is_empty());
+    }
+
+    #[test]
+    fn load_version_3_turns() {
+        let board = Board::from_seed(BoardConfig::default(), 1);
+        let mut json = legacy_json(&board, 3);
+        let file = json.as_object_mut().unwrap();
+        let old_turns = serde_json::json!([
+            { "Search": { "at": { "x": 0, "y": 0 }, "outcome": { "Distance": 5 } } },
+            { "Move": { "from": { "x": 0, "y": 0 }, "to": { "x": 1, "y": 1 }, "distance": 1 } },
+            { "Search": { "at": { "x": 1, "y": 1 }, "outcome": "Found" } }
+        ]);
+        file.insert("turns".to_string(), old_turns);
+
+        let loaded = Board::from_json(&json.to_string()).unwrap();
+        assert_eq!(loaded.turns().len(), 3);
+        assert_eq!(
+            loaded.turns()[0],
+            Turn::Search {
+                at: Point { x: 0, y: 0 },
+                outcome: SearchOutcome::Distance(5, None),
+                player: 0,
+            }
+        );
+        assert_eq!(loaded.score(0).distance_walked, 1);
+        assert_eq!(loaded.treasures, board.treasures);
+    }
+
+    #[test]
+    fn load_version_4_treasure() {
+        let mut board = Board::from_seed(BoardConfig::default(), 1);
+        board.treasures[0].found = true;
+        let loaded = Board::from_json(&legacy_json(&board, 4).to_string()).unwrap();
+        assert_eq!(loaded.treasures, board.treasures);
+        assert!(loaded.is_treasure_found());
+        assert_eq!(loaded.players.len(), 1);
+        assert_eq!(loaded.players[0].start, board.players[0].start);
+    }
+
+    #[test]
+    fn save_to_file() {
+        let path = std::env::temp_dir().join(format!("lab01_save_{}.json", std::process::id()));
+        let board = Board::from_seed(BoardConfig::default(), 1);
+        board.save(&path).unwrap();
+        let loaded = Board::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.treasures, board.treasures);
+    }
+
+    #[test]
+    fn reject_other_versions() {
+        let board = Board::from_seed(BoardConfig::default(), 1);
+        let json = board
+            .to_json()
+            .unwrap()
+            .replace(&format!("\"version\": {}", SAVE_VERSION), "\"version\": 99");
+        let err = Board::from_json(&json).unwrap_err();
+        assert!(matches!(
+            err,
+            SaveError::Version {
+                found: 99,
+                expected: SAVE_VERSION
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "The save file uses version 99 of the format, versions 1 to {} are supported",
+                SAVE_VERSION
+            )
+        );
+    }
+
+    #[test]
+    fn reject_out_of_bounds_treasure() {
+        let mut board = Board::from_seed(BoardConfig::default(), 1);
+        board.treasures[0].position = Point { x: 15, y: 0 };
+        assert!(matches!(
+            Board::from_json(&board.to_json().unwrap()),
+            Err(SaveError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn reject_out_of_bounds_turns() {
+        let config = BoardConfig::new(5, 5, 2).with_practice(true);
+        let mut board = Board::from_seed(config, 1);
+        board.search();
+        board.turns[0] = Turn::Search {
+            at: Point { x: 40, y: 40 },
+            outcome: SearchOutcome::Distance(1, None),
+            player: 0,
+        };
+        assert!(matches!(
+            Board::from_json(&board.to_json().unwrap()),
+            Err(SaveError::Invalid(_))
+        ));
+
+        let mut board = Board::from_seed(config.with_drift(Some(1)), 1);
+        board.search();
+        board.drifts.push(Drift {
+            turn: 1,
+            treasure: 0,
+            from: Point { x: 5, y: 0 },
+            to: Point { x: 4, y: 0 },
+        });
+        assert!(matches!(
+            Board::from_json(&board.to_json().unwrap()),
+            Err(SaveError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn reject_no_move_range() {
+        let board = Board::from_seed(BoardConfig::new(5, 5, 0), 1);
+        assert!(matches!(
+            Board::from_json(&board.to_json().unwrap()),
+            Err(SaveError::Invalid(_))
+        ));
+    }
+}
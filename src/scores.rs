@@ -0,0 +1,240 @@
+//! Local high-score table
+//!
+//! Finished games are appended to a JSON file so that runs can be compared across sessions.
+//! Entries are grouped by board size and difficulty, only games played with the same settings
+//! are ranked against each other.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::BoardConfig;
+use crate::history::Score;
+
+/// Default location of the leaderboard, relative to the working directory
+pub const SCORES_FILE: &str = "lab01_scores.json";
+
+/// Version of the leaderboard format, bump it whenever `ScoreEntry` changes
+pub const SCORES_VERSION: u32 = 1;
+
+/// Settings a game is ranked under
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Category {
+    pub width: u8,
+    pub height: u8,
+    pub difficulty: String,
+}
+
+impl Category {
+    pub fn new(config: &BoardConfig, difficulty: &str) -> Self {
+        Self {
+            width: config.width,
+            height: config.height,
+            difficulty: difficulty.to_string(),
+        }
+    }
+}
+
+/// One finished game in the leaderboard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreEntry {
+    pub name: String,
+    pub category: Category,
+    pub turns: u32,
+    pub searches: u32,
+    /// Day the game was won, as YYYY-MM-DD
+    pub date: String,
+}
+
+impl ScoreEntry {
+    /// Creates an entry dated today
+    pub fn new(name: &str, category: Category, score: &Score) -> Self {
+        Self {
+            name: name.to_string(),
+            category,
+            turns: score.turns,
+            searches: score.searches,
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+/// Reasons for the leaderboard at `path` to be left unread or unwritten
+#[derive(Debug)]
+pub enum ScoresError {
+    Io {
+        path: PathBuf,
+        err: io::Error,
+    },
+    Json {
+        path: PathBuf,
+        err: serde_json::Error,
+    },
+    /// The file was written with a newer version of the format
+    Version {
+        path: PathBuf,
+        found: u32,
+    },
+}
+
+impl fmt::Display for ScoresError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScoresError::Io { path, err } => write!(
+                f,
+                "Could not access the leaderboard {}: {}",
+                path.display(),
+                err
+            ),
+            ScoresError::Json { path, err } => write!(
+                f,
+                "The leaderboard {} is corrupted: {}",
+                path.display(),
+                err
+            ),
+            ScoresError::Version { path, found } => write!(
+                f,
+                "The leaderboard {} uses version {} of the format, versions 1 to {} are supported",
+                path.display(),
+                found,
+                SCORES_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for ScoresError {}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    version: u32,
+    entries: Vec<ScoreEntry>,
+}
+
+impl Leaderboard {
+    /// Reads the leaderboard at `path`, a missing file is an empty leaderboard
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ScoresError> {
+        let path = path.as_ref();
+        let json = match fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(ScoresError::Io {
+                    path: path.to_path_buf(),
+                    err,
+                })
+            }
+        };
+        let leaderboard: Self = serde_json::from_str(&json).map_err(|err| ScoresError::Json {
+            path: path.to_path_buf(),
+            err,
+        })?;
+        if leaderboard.version == 0 || leaderboard.version > SCORES_VERSION {
+            return Err(ScoresError::Version {
+                path: path.to_path_buf(),
+                found: leaderboard.version,
+            });
+        }
+        Ok(leaderboard)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ScoresError> {
+        let path = path.as_ref();
+        let file = Self {
+            version: SCORES_VERSION,
+            entries: self.entries.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file).map_err(|err| ScoresError::Json {
+            path: path.to_path_buf(),
+            err,
+        })?;
+        fs::write(path, json).map_err(|err| ScoresError::Io {
+            path: path.to_path_buf(),
+            err,
+        })
+    }
+
+    pub fn add(&mut self, entry: ScoreEntry) {
+        self.entries.push(entry);
+    }
+
+    /// The `n` best games of a category: fewest turns first, then fewest searches,
+    /// ties keep the oldest game first
+    pub fn top(&self, category: &Category, n: usize) -> Vec<&ScoreEntry> {
+        let mut entries: Vec<&ScoreEntry> = self
+            .entries
+            .iter()
+            .filter(|entry| &entry.category == category)
+            .collect();
+        entries.sort_by_key(|entry| (entry.turns, entry.searches));
+        entries.truncate(n);
+        entries
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(name: &str, category: &Category, turns: u32, searches: u32) -> ScoreEntry {
+        ScoreEntry {
+            name: name.to_string(),
+            category: category.clone(),
+            turns,
+            searches,
+            date: "2021-03-14".to_string(),
+        }
+    }
+
+    #[test]
+    fn top_entries() {
+        let small = Category::new(&BoardConfig::new(8, 8, 4), "normal");
+        let large = Category::new(&BoardConfig::new(64, 64, 4), "normal");
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.add(entry("slow", &small, 9, 3));
+        leaderboard.add(entry("fast", &small, 4, 2));
+        leaderboard.add(entry("other board", &large, 1, 1));
+        leaderboard.add(entry("lucky", &small, 4, 1));
+        leaderboard.add(entry("late", &small, 4, 1));
+
+        let names: Vec<&str> = leaderboard
+            .top(&small, 3)
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names, ["lucky", "late", "fast"]);
+    }
+
+    #[test]
+    fn leaderboard_file() {
+        let path = std::env::temp_dir().join(format!("lab01_scores_{}.json", std::process::id()));
+        let category = Category::new(&BoardConfig::default(), "normal");
+        assert!(Leaderboard::load(&path)
+            .unwrap()
+            .top(&category, 10)
+            .is_empty());
+
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.add(entry("alice", &category, 5, 2));
+        leaderboard.save(&path).unwrap();
+        let loaded = Leaderboard::load(&path).unwrap();
+        fs::write(&path, "").unwrap();
+        let truncated = Leaderboard::load(&path).unwrap_err();
+        fs::write(&path, r#"{ "version": 9, "entries": [] }"#).unwrap();
+        let newer = Leaderboard::load(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            loaded.top(&category, 10),
+            [&entry("alice", &category, 5, 2)]
+        );
+        assert!(matches!(truncated, ScoresError::Json { .. }));
+        assert!(truncated
+            .to_string()
+            .starts_with(&format!("The leaderboard {} is corrupted", path.display())));
+        assert!(matches!(newer, ScoresError::Version { found: 9, .. }));
+    }
+}
@@ -0,0 +1,476 @@
+//! Walls and water inside the board
+//!
+//! Obstacles are drawn as short straight segments of a single terrain, and a square only becomes
+//! an obstacle if every square left passable can still be walked to from the others: no treasure
+//! is ever out of reach. Players walk around the obstacles one step at a time, a diagonal step
+//! being allowed when the metric counts it as a single bloc and it does not squeeze between two
+//! obstacles.
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+use rand::Rng;
+
+use crate::board::Board;
+use crate::utils::{Metric, Point};
+
+/// Longest segment of wall or water
+const MAX_SEGMENT: u8 = 4;
+
+/// Segments drawn per obstacle square asked for before giving up on a crowded board
+const MAX_ATTEMPTS: usize = 10;
+
+/// How far from a new obstacle `has_detour` looks for a way around it
+const DETOUR_RADIUS: u8 = 6;
+
+const SIDES: [(i16, i16); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const DIAGONALS: [(i16, i16); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// Whether a player can stand on a square of the grid holding `c`
+pub fn is_passable(c: char) -> bool {
+    c != Board::WALL_CHAR && c != Board::WATER_CHAR
+}
+
+/// Whether `to` is a single step away from `from`: a passable square on one of its sides, or on
+/// one of its corners when the metric counts a diagonal step as a single bloc and the step does
+/// not squeeze between two obstacles
+pub fn is_step(grid: &[Vec<char>], metric: Metric, from: Point, to: Point) -> bool {
+    let passable = |x: u8, y: u8| {
+        (x as usize) < grid.len()
+            && (y as usize) < grid[0].len()
+            && is_passable(grid[x as usize][y as usize])
+    };
+    let (dx, dy) = (from.x.abs_diff(to.x), from.y.abs_diff(to.y));
+    match (dx, dy) {
+        (0, 1) | (1, 0) => passable(to.x, to.y),
+        (1, 1) => {
+            metric != Metric::Manhattan
+                && passable(to.x, to.y)
+                && (passable(to.x, from.y) || passable(from.x, to.y))
+        }
+        _ => false,
+    }
+}
+
+/// One of the shortest walks from `from` to `to` in at most `limit` steps, as the squares
+/// stepped on after `from`
+///
+/// Among the shortest walks, the one staying the closest to the straight line is preferred.
+pub fn route(
+    grid: &[Vec<char>],
+    metric: Metric,
+    from: Point,
+    to: Point,
+    limit: u16,
+) -> Option<Vec<Point>> {
+    let distances = walking_distances(grid, metric, from, limit);
+    let mut distance = distances[to.x as usize][to.y as usize]?;
+    let mut route = vec![to];
+    let mut square = to;
+    // walk back from the destination, one square closer to the start at a time
+    while distance > 0 {
+        square = around(grid, square)
+            .filter(|p| {
+                distances[p.x as usize][p.y as usize] == Some(distance - 1)
+                    && is_step(grid, metric, *p, square)
+            })
+            .min_by_key(|p| straying(from, to, *p))
+            .expect("a square one step closer to the start is on the way");
+        distance -= 1;
+        route.push(square);
+    }
+    route.pop();
+    route.reverse();
+    Some(route)
+}
+
+/// The squares a single step away from `from`, see `is_step`
+pub fn steps(grid: &[Vec<char>], metric: Metric, from: Point) -> Vec<Point> {
+    around(grid, from)
+        .filter(|&to| is_step(grid, metric, from, to))
+        .collect()
+}
+
+/// The squares of `grid` around `square`, the ones on its sides first
+fn around(grid: &[Vec<char>], square: Point) -> impl Iterator<Item = Point> + '_ {
+    SIDES.iter().chain(&DIAGONALS).filter_map(move |(dx, dy)| {
+        let x = u8::try_from(square.x as i16 + dx).ok()?;
+        let y = u8::try_from(square.y as i16 + dy).ok()?;
+        ((x as usize) < grid.len() && (y as usize) < grid[0].len()).then_some(Point { x, y })
+    })
+}
+
+/// How far `p` is from the straight line between `from` and `to`, doubled area of the triangle
+fn straying(from: Point, to: Point, p: Point) -> i32 {
+    let (ax, ay) = (to.x as i32 - from.x as i32, to.y as i32 - from.y as i32);
+    let (bx, by) = (p.x as i32 - from.x as i32, p.y as i32 - from.y as i32);
+    (ax * by - ay * bx).abs()
+}
+
+/// Number of steps of the shortest walk from `from` to every square of `grid`, indexed as
+/// `grid[x][y]`
+///
+/// Squares that are obstacles, can not be reached or are more than `limit` steps away are None.
+pub fn walking_distances(
+    grid: &[Vec<char>],
+    metric: Metric,
+    from: Point,
+    limit: u16,
+) -> Vec<Vec<Option<u16>>> {
+    walk(grid, metric, from, limit, |_, _| true)
+}
+
+/// The squares matching `is_goal` that are the fewest steps away from `from`, on foot
+///
+/// Stops walking as soon as they are found, which is much faster than `walking_distances` on
+/// large boards. Empty when no goal can be reached.
+pub fn closest_on_foot<F: Fn(&Point) -> bool>(
+    grid: &[Vec<char>],
+    metric: Metric,
+    from: Point,
+    is_goal: F,
+) -> Vec<Point> {
+    let mut goals = Vec::new();
+    let mut goal_distance = None;
+    walk(grid, metric, from, u16::MAX, |square, distance| {
+        if goal_distance.is_some_and(|goal_distance| distance > goal_distance) {
+            return false;
+        }
+        if is_goal(&square) {
+            goals.push(square);
+            goal_distance = Some(distance);
+        }
+        true
+    });
+    goals
+}
+
+/// Walks from `from` to the squares up to `limit` steps away, closest first, and returns their
+/// distances
+///
+/// `visit` is called on every square reached with its distance, the walk stops once it returns
+/// false.
+fn walk<F: FnMut(Point, u16) -> bool>(
+    grid: &[Vec<char>],
+    metric: Metric,
+    from: Point,
+    limit: u16,
+    mut visit: F,
+) -> Vec<Vec<Option<u16>>> {
+    let (width, height) = (grid.len(), grid[0].len());
+    let mut distances = vec![vec![None; height]; width];
+    if !is_passable(grid[from.x as usize][from.y as usize]) {
+        return distances;
+    }
+    distances[from.x as usize][from.y as usize] = Some(0);
+    let mut queue = VecDeque::from(vec![(from, 0)]);
+    while let Some((square, distance)) = queue.pop_front() {
+        if !visit(square, distance) {
+            break;
+        }
+        if distance == limit {
+            continue;
+        }
+        for next in around(grid, square) {
+            if distances[next.x as usize][next.y as usize].is_none()
+                && is_step(grid, metric, square, next)
+            {
+                distances[next.x as usize][next.y as usize] = Some(distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// Whether the passable squares around (x,y) are linked to each other without going through
+/// it, in which case turning it into an obstacle can not cut the board in two
+///
+/// Two squares next to each other on the ring around (x,y) are always one step apart, so the
+/// passable squares are linked when they form a single run around the ring.
+fn is_locally_connected(grid: &[Vec<char>], x: usize, y: usize) -> bool {
+    const RING: [(i16, i16); 8] = [
+        (0, 1),
+        (1, 1),
+        (1, 0),
+        (1, -1),
+        (0, -1),
+        (-1, -1),
+        (-1, 0),
+        (-1, 1),
+    ];
+    let passable: Vec<bool> = RING
+        .iter()
+        .map(|(dx, dy)| {
+            let (nx, ny) = (x as i16 + dx, y as i16 + dy);
+            nx >= 0
+                && ny >= 0
+                && (nx as usize) < grid.len()
+                && (ny as usize) < grid[0].len()
+                && is_passable(grid[nx as usize][ny as usize])
+        })
+        .collect();
+    let runs = (0..RING.len())
+        .filter(|&i| passable[i] && !passable[(i + RING.len() - 1) % RING.len()])
+        .count();
+    runs <= 1
+}
+
+/// Whether the passable squares around `square`, which just became an obstacle, can still walk
+/// to each other without going further than `DETOUR_RADIUS` blocs from it
+///
+/// Then the obstacle cut no part of the board off. A longer detour is missed and the obstacle
+/// refused, but the check does not depend on the size of the board.
+fn has_detour(grid: &[Vec<char>], metric: Metric, square: Point) -> bool {
+    let around: Vec<Point> = around(grid, square)
+        .filter(|p| is_passable(grid[p.x as usize][p.y as usize]))
+        .collect();
+    let start = match around.first() {
+        Some(&start) => start,
+        None => return true,
+    };
+    let side = 2 * DETOUR_RADIUS as usize + 1;
+    // index of a square of the window centered on `square`, None outside of it
+    let index = |p: Point| {
+        let dx = p.x as i16 - square.x as i16 + DETOUR_RADIUS as i16;
+        let dy = p.y as i16 - square.y as i16 + DETOUR_RADIUS as i16;
+        let inside = (0..side as i16).contains(&dx) && (0..side as i16).contains(&dy);
+        inside.then(|| dx as usize * side + dy as usize)
+    };
+    let mut seen = vec![false; side * side];
+    seen[index(start).unwrap()] = true;
+    let mut queue = VecDeque::from(vec![start]);
+    let mut left = around.len() - 1;
+    while let Some(p) = queue.pop_front() {
+        if left == 0 {
+            break;
+        }
+        for next in steps(grid, metric, p) {
+            if let Some(i) = index(next).filter(|&i| !seen[i]) {
+                seen[i] = true;
+                if around.contains(&next) {
+                    left -= 1;
+                }
+                queue.push_back(next);
+            }
+        }
+    }
+    left == 0
+}
+
+/// Whether every passable square of `grid` can be walked to from every other one
+#[cfg(test)]
+fn is_connected(grid: &[Vec<char>], metric: Metric) -> bool {
+    let mut squares = (0..grid.len()).flat_map(|x| (0..grid[x].len()).map(move |y| (x, y)));
+    let start = match squares.find(|&(x, y)| is_passable(grid[x][y])) {
+        Some((x, y)) => Point {
+            x: x as u8,
+            y: y as u8,
+        },
+        None => return true,
+    };
+    let distances = walking_distances(grid, metric, start, u16::MAX);
+    grid.iter().zip(distances).all(|(column, reached)| {
+        column
+            .iter()
+            .zip(reached)
+            .all(|(c, distance)| !is_passable(*c) || distance.is_some())
+    })
+}
+
+/// Turns about `percent`% of the squares of `grid` into segments of wall or water
+///
+/// The squares of `keep`, where the treasures and the players are, stay passable, and so does
+/// any square needed to walk between the others. Fewer obstacles are placed when the board is
+/// too crowded to fit them all.
+pub fn place_obstacles<R: Rng + ?Sized>(
+    grid: &mut [Vec<char>],
+    metric: Metric,
+    percent: u8,
+    keep: &[Point],
+    rng: &mut R,
+) {
+    let (width, height) = (grid.len(), grid[0].len());
+    let wanted = width * height * percent.min(100) as usize / 100;
+    let mut placed = 0;
+    for _ in 0..wanted * MAX_ATTEMPTS {
+        if placed == wanted {
+            break;
+        }
+        let terrain = if rng.gen_bool(0.5) {
+            Board::WALL_CHAR
+        } else {
+            Board::WATER_CHAR
+        };
+        let (mut x, mut y) = (rng.gen_range(0..width), rng.gen_range(0..height));
+        let horizontal = rng.gen_bool(0.5);
+        for _ in 0..rng.gen_range(1..=MAX_SEGMENT) {
+            if x >= width || y >= height || placed == wanted {
+                break;
+            }
+            let square = Point {
+                x: x as u8,
+                y: y as u8,
+            };
+            if is_passable(grid[x][y]) && !keep.contains(&square) {
+                let previous = grid[x][y];
+                let local = is_locally_connected(grid, x, y);
+                grid[x][y] = terrain;
+                if !local && !has_detour(grid, metric, square) {
+                    grid[x][y] = previous;
+                    break;
+                }
+                placed += 1;
+            }
+            if horizontal {
+                x += 1;
+            } else {
+                y += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rstest::rstest;
+
+    /// Grid from rows written top row first, as the board is printed
+    fn grid(rows: &[&str]) -> Vec<Vec<char>> {
+        let height = rows.len();
+        let mut grid = vec![vec![Board::EMPTY_CHAR; height]; rows[0].len()];
+        for (i, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                grid[x][height - 1 - i] = c;
+            }
+        }
+        grid
+    }
+
+    #[rstest(
+        metric,
+        expected,
+        case(Metric::Chebyshev, Some(7)),
+        case(Metric::Euclidean, Some(7)),
+        case(Metric::Manhattan, Some(10)),
+        ::trace
+    )]
+    fn walk_around_wall(metric: Metric, expected: Option<u16>) {
+        let grid = grid(&["....", ".#~.", ".#..", ".#.."]);
+        let distances = walking_distances(&grid, metric, Point { x: 0, y: 0 }, u16::MAX);
+        assert_eq!(distances[2][0], expected);
+        assert_eq!(distances[1][1], None);
+    }
+
+    #[test]
+    fn closest_goals() {
+        let grid = grid(&["....", ".#~.", ".#..", ".#.."]);
+        let goals = closest_on_foot(&grid, Metric::Chebyshev, Point { x: 0, y: 0 }, |p| p.x >= 2);
+        assert_eq!(goals, vec![Point { x: 2, y: 3 }]);
+        let goals = closest_on_foot(&grid, Metric::Chebyshev, Point { x: 0, y: 0 }, |p| {
+            p.x == 1 && p.y < 3
+        });
+        assert!(goals.is_empty());
+    }
+
+    #[test]
+    fn no_squeezing_between_obstacles() {
+        let grid = grid(&["#.", ".#"]);
+        let distances = walking_distances(&grid, Metric::Chebyshev, Point { x: 0, y: 0 }, 5);
+        assert_eq!(distances[1][1], None);
+        let grid = self::grid(&["#.", ".."]);
+        let distances = walking_distances(&grid, Metric::Chebyshev, Point { x: 0, y: 0 }, 5);
+        assert_eq!(distances[1][1], Some(1));
+    }
+
+    #[rstest(
+        rows,
+        expected,
+        case(&["...", "...", "..."], true),
+        case(&["#..", "#..", "###"], true),
+        case(&[".#.", "...", "..."], true),
+        case(&[".#.", "#.#", ".#."], false),  // the corners are cut off from each other
+        case(&["...", "#.#", "..."], false),
+        ::trace
+    )]
+    fn local_connection_tests(rows: &[&str], expected: bool) {
+        assert_eq!(is_locally_connected(&grid(rows), 1, 1), expected);
+    }
+
+    #[rstest(
+        metric,
+        expected,
+        case(Metric::Chebyshev, vec![(1, 1), (2, 1), (3, 2)]),
+        case(Metric::Manhattan, vec![(1, 0), (1, 1), (2, 1), (2, 2), (3, 2)]),
+        ::trace
+    )]
+    fn straight_routes(metric: Metric, expected: Vec<(u8, u8)>) {
+        let grid = grid(&["....", "....", "....", "...."]);
+        let route = route(&grid, metric, Point { x: 0, y: 0 }, Point { x: 3, y: 2 }, 9);
+        let expected: Vec<Point> = expected.into_iter().map(|(x, y)| Point { x, y }).collect();
+        assert_eq!(route, Some(expected));
+    }
+
+    #[test]
+    fn route_around_wall() {
+        let grid = grid(&["....", ".#~.", ".#..", ".#.."]);
+        let from = Point { x: 0, y: 0 };
+        let to = Point { x: 2, y: 0 };
+        assert_eq!(route(&grid, Metric::Chebyshev, from, to, 6), None);
+        let route = route(&grid, Metric::Chebyshev, from, to, 7).unwrap();
+        assert_eq!(route.len(), 7);
+        assert_eq!(route.last(), Some(&to));
+        assert!(is_step(&grid, Metric::Chebyshev, from, route[0]));
+        assert!(route
+            .windows(2)
+            .all(|step| is_step(&grid, Metric::Chebyshev, step[0], step[1])));
+    }
+
+    #[test]
+    fn walk_limit() {
+        let grid = grid(&["....."]);
+        let distances = walking_distances(&grid, Metric::Chebyshev, Point { x: 0, y: 0 }, 3);
+        assert_eq!(distances[3][0], Some(3));
+        assert_eq!(distances[4][0], None);
+    }
+
+    #[test]
+    fn obstacles_on_large_boards() {
+        // the detours are only checked around each obstacle, yet the whole board stays connected
+        let mut grid = vec![vec![Board::EMPTY_CHAR; 255]; 255];
+        place_obstacles(
+            &mut grid,
+            Metric::Chebyshev,
+            50,
+            &[],
+            &mut StdRng::seed_from_u64(1),
+        );
+        let obstacles = grid.iter().flatten().filter(|c| !is_passable(**c)).count();
+        assert_eq!(obstacles, 255 * 255 * 50 / 100);
+        assert!(is_connected(&grid, Metric::Chebyshev));
+    }
+
+    #[rstest(metric, case(Metric::Chebyshev), case(Metric::Manhattan), ::trace)]
+    fn obstacles_keep_board_connected(metric: Metric) {
+        for seed in 0..20 {
+            let mut grid = vec![vec![Board::EMPTY_CHAR; 10]; 12];
+            let keep = [Point { x: 0, y: 0 }, Point { x: 11, y: 9 }];
+            place_obstacles(
+                &mut grid,
+                metric,
+                40,
+                &keep,
+                &mut StdRng::seed_from_u64(seed),
+            );
+            let obstacles = grid.iter().flatten().filter(|c| !is_passable(**c)).count();
+            assert!(obstacles > 30 && obstacles <= 48, "{} obstacles", obstacles);
+            assert!(keep
+                .iter()
+                .all(|p| is_passable(grid[p.x as usize][p.y as usize])));
+            assert!(is_connected(&grid, metric));
+        }
+    }
+}
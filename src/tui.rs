@@ -0,0 +1,424 @@
+//! Full-screen front end
+//!
+//! The board stays in place while a cursor picks the destination of the next move: arrow keys
+//! or WASD move the cursor within reach of the player, Enter moves there and Space searches.
+//! A side panel shows the last search reply and the turns played so far.
+
+use std::io;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color as TuiColor, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use termcolor::Color;
+
+use crate::board::Board;
+use crate::bot::Bot;
+use crate::history::Turn;
+use crate::outcome::SearchOutcome;
+use crate::presenter;
+use crate::utils::Point;
+
+/// State of the full-screen game on top of the board
+pub struct App<'a> {
+    board: &'a mut Board,
+    /// Computer player taking its turns between the ones of the players at the keyboard
+    bot: Option<&'a mut Bot>,
+    /// Destination of the next move, always within reach of the current player
+    cursor: Point,
+    /// Reply to the last search, or the reason the last move was refused
+    message: String,
+    quit: bool,
+}
+
+impl<'a> App<'a> {
+    pub fn new(board: &'a mut Board, bot: Option<&'a mut Bot>) -> Self {
+        let cursor = board.player().position;
+        let mut app = Self {
+            board,
+            bot,
+            cursor,
+            message: String::from("Good luck!"),
+            quit: false,
+        };
+        app.play_bot();
+        app
+    }
+
+    /// Whether the game is over or the player left it
+    pub fn is_done(&self) -> bool {
+        self.quit || self.board.is_over()
+    }
+
+    /// Applies a key pressed by the player
+    pub fn handle_key(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Up | KeyCode::Char('w') => self.move_cursor(0, 1),
+            KeyCode::Down | KeyCode::Char('s') => self.move_cursor(0, -1),
+            KeyCode::Left | KeyCode::Char('a') => self.move_cursor(-1, 0),
+            KeyCode::Right | KeyCode::Char('d') => self.move_cursor(1, 0),
+            KeyCode::Enter => {
+                let (x, y) = self.cursor.as_tuple();
+                match self.board.move_to(x, y) {
+                    Ok(()) => self.message = format!("Moved to ({},{}).", x, y),
+                    Err(err) => self.message = err.to_string(),
+                }
+                self.end_turn();
+            }
+            KeyCode::Char(' ') => {
+                let outcome = self.board.search();
+                self.message = search_message(outcome, self.board.treasures_left());
+                self.end_turn();
+            }
+            KeyCode::Char('u') => {
+                match self.board.undo() {
+                    Ok(_) => self.message = String::from("Took back the last turn."),
+                    Err(err) => self.message = err.to_string(),
+                }
+                self.cursor = self.board.player().position;
+            }
+            KeyCode::Char('r') => {
+                match self.board.redo() {
+                    Ok(_) => self.message = String::from("Played the turn again."),
+                    Err(err) => self.message = err.to_string(),
+                }
+                self.cursor = self.board.player().position;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.quit = true,
+            _ => {}
+        }
+    }
+
+    /// Moves the cursor by one square, unless it would leave the board or the reach of the player
+    fn move_cursor(&mut self, dx: i16, dy: i16) {
+        let x = self.cursor.x as i16 + dx;
+        let y = self.cursor.y as i16 + dy;
+        if x < 0 || y < 0 || x > u8::MAX as i16 || y > u8::MAX as i16 {
+            return;
+        }
+        let (x, y) = (x as u8, y as u8);
+        if self.board.is_within_bounds(x, y).is_ok()
+            && self.board.get_distance_to(x, y) <= self.board.config().max_dist as u16
+        {
+            self.cursor = Point { x, y };
+        }
+    }
+
+    /// Lets the computer play if it is its turn, then puts the cursor on the next player
+    fn end_turn(&mut self) {
+        self.play_bot();
+        self.cursor = self.board.player().position;
+        if self.board.is_lost() {
+            self.message = String::from("Out of stamina, the game is lost! Press any key.");
+        }
+    }
+
+    fn play_bot(&mut self) {
+        if let Some(bot) = self.bot.as_mut() {
+            while !self.board.is_over() && bot.player() == self.board.current_player() {
+                bot.play(self.board);
+            }
+        }
+    }
+
+    /// Draws the board on the left and the side panel on the right
+    pub fn draw(&self, frame: &mut Frame) {
+        let config = self.board.config();
+        let grid_width = 3 * config.width as u16 + 5;
+        let [grid_area, panel_area] =
+            Layout::horizontal([Constraint::Length(grid_width), Constraint::Min(30)])
+                .areas(frame.area());
+        frame.render_widget(self.grid(), grid_area);
+        self.draw_panel(frame, panel_area);
+    }
+
+    fn grid(&self) -> Paragraph<'_> {
+        let config = self.board.config();
+        let mut lines = Vec::new();
+        for y in (0..config.height).rev() {
+            let mut spans = vec![Span::raw(format!("{:>2} ", y))];
+            for x in 0..config.width {
+                let (c, color) = presenter::square(self.board, x, y);
+                let mut style = Style::default().fg(tui_color(color));
+                if (x, y) == self.cursor.as_tuple() {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                spans.push(Span::styled(format!("{:^3}", c), style));
+            }
+            lines.push(Line::from(spans));
+        }
+        let mut columns = String::from("   ");
+        for x in 0..config.width {
+            columns.push_str(&format!("{:^3}", x));
+        }
+        lines.push(Line::raw(columns));
+        Paragraph::new(lines).block(Block::bordered().title(" Treasure Hunt "))
+    }
+
+    fn draw_panel(&self, frame: &mut Frame, area: Rect) {
+        let [status_area, history_area, keys_area] = Layout::vertical([
+            Constraint::Length(8),
+            Constraint::Min(3),
+            Constraint::Length(5),
+        ])
+        .areas(area);
+
+        let player = self.board.player();
+        let mut status = vec![
+            Line::styled(
+                format!("Player {}", self.board.current_player() + 1),
+                Style::default().fg(tui_color(player.color)),
+            ),
+            Line::raw(format!("Treasures left: {}", self.board.treasures_left())),
+        ];
+        if let (Some(stamina), Some(left)) = (
+            self.board.config().stamina,
+            self.board.stamina_left(self.board.current_player()),
+        ) {
+            status.push(Line::raw(format!("Stamina: {}/{}", left, stamina)));
+        }
+        // the reply of the last search stays on screen after the moves
+        let current = self.board.current_player();
+        if let Some(search) = self
+            .board
+            .turns()
+            .iter()
+            .rev()
+            .find(|turn| turn.player() == current && matches!(turn, Turn::Search { .. }))
+        {
+            status.push(Line::raw(format!(
+                "Last search: {}",
+                describe_search(search)
+            )));
+        }
+        status.push(Line::raw(self.message.clone()));
+        frame.render_widget(
+            Paragraph::new(status)
+                .wrap(Wrap { trim: true })
+                .block(Block::bordered().title(" Status ")),
+            status_area,
+        );
+
+        // the latest turns that fit, oldest first
+        let turns = self.board.turns();
+        let shown = (history_area.height.saturating_sub(2) as usize).min(turns.len());
+        let history: Vec<Line> = turns
+            .iter()
+            .enumerate()
+            .skip(turns.len() - shown)
+            .map(|(i, turn)| Line::raw(format!("{:>3}. {}", i + 1, describe_turn(turn))))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(history).block(Block::bordered().title(" Turns ")),
+            history_area,
+        );
+
+        let mut keys = vec![
+            Line::raw("Arrows/WASD: pick a square, Enter: move"),
+            Line::raw("Space: search, Q/Esc: quit"),
+        ];
+        if self.board.config().practice {
+            keys.push(Line::raw("U: undo, R: redo"));
+        }
+        frame.render_widget(
+            Paragraph::new(keys).block(Block::bordered().title(" Keys ")),
+            keys_area,
+        );
+    }
+}
+
+/// One line summary of a turn for the history panel
+fn describe_turn(turn: &Turn) -> String {
+    let player = turn.player() + 1;
+    match turn {
+        Turn::Move { to, .. } => format!("P{} moved to ({},{})", player, to.x, to.y),
+        Turn::Search {
+            at,
+            outcome: SearchOutcome::Found,
+            ..
+        } => format!("P{} found a treasure at ({},{})", player, at.x, at.y),
+        Turn::Search { .. } => format!("P{} searched {}", player, describe_search(turn)),
+    }
+}
+
+/// Square and reply of a search, with its hint
+fn describe_search(turn: &Turn) -> String {
+    match turn {
+        Turn::Search {
+            at,
+            outcome: SearchOutcome::Found,
+            ..
+        } => format!("({},{}): found a treasure", at.x, at.y),
+        Turn::Search {
+            at,
+            outcome: SearchOutcome::Distance(distance, None),
+            ..
+        } => format!("({},{}): {} blocs", at.x, at.y, distance),
+        Turn::Search {
+            at,
+            outcome: SearchOutcome::Distance(distance, Some(hint)),
+            ..
+        } => format!("({},{}): {} blocs, {}", at.x, at.y, distance, hint),
+        Turn::Move { .. } => String::new(),
+    }
+}
+
+/// Reply to a search, `remaining` being the number of treasures still hidden
+fn search_message(outcome: SearchOutcome, remaining: usize) -> String {
+    match outcome {
+        SearchOutcome::Found if remaining == 0 => {
+            String::from("You found the last treasure! Press any key.")
+        }
+        SearchOutcome::Found => format!("You found a treasure! {} more to find.", remaining),
+        SearchOutcome::Distance(distance, None) => {
+            format!("The treasure is {} blocs away.", distance)
+        }
+        SearchOutcome::Distance(distance, Some(hint)) => {
+            format!("The treasure is {} blocs away, {}.", distance, hint)
+        }
+    }
+}
+
+fn tui_color(color: Color) -> TuiColor {
+    match color {
+        Color::Black => TuiColor::Black,
+        Color::Blue => TuiColor::Blue,
+        Color::Green => TuiColor::Green,
+        Color::Red => TuiColor::Red,
+        Color::Cyan => TuiColor::Cyan,
+        Color::Magenta => TuiColor::Magenta,
+        Color::Yellow => TuiColor::Yellow,
+        Color::Rgb(r, g, b) => TuiColor::Rgb(r, g, b),
+        Color::Ansi256(n) => TuiColor::Indexed(n),
+        _ => TuiColor::White,
+    }
+}
+
+/// Plays `board` full screen until the game is over or the player quits
+///
+/// The terminal is restored before returning, even if drawing failed.
+pub fn run(board: &mut Board, bot: Option<&mut Bot>) -> io::Result<()> {
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, App::new(board, bot));
+    ratatui::try_restore()?;
+    result
+}
+
+fn event_loop(terminal: &mut DefaultTerminal, mut app: App) -> io::Result<()> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            // the final board stays on screen until a key is pressed
+            if app.board.is_over() {
+                return Ok(());
+            }
+            app.handle_key(key.code);
+            if app.quit {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::board::BoardConfig;
+    use crate::outcome::HintMode;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn board() -> Board {
+        let mut board = Board::from_seed(BoardConfig::new(6, 6, 2), 1);
+        board.treasures[0].position.set((5, 5));
+        board.players[0].position.set((1, 1));
+        board
+    }
+
+    /// Draws `app` on an 80x20 terminal and returns its cells row after row
+    fn screen(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn cursor_stays_in_reach() {
+        let mut board = board();
+        let mut app = App::new(&mut board, None);
+        for _ in 0..5 {
+            app.handle_key(KeyCode::Up);
+        }
+        assert_eq!(app.cursor.as_tuple(), (1, 3));
+        for _ in 0..5 {
+            app.handle_key(KeyCode::Char('a'));
+        }
+        assert_eq!(app.cursor.as_tuple(), (0, 3));
+        app.handle_key(KeyCode::Down);
+        app.handle_key(KeyCode::Char('d'));
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.board.player().position.as_tuple(), (1, 2));
+        // the cursor follows the player
+        assert_eq!(app.cursor.as_tuple(), (1, 2));
+    }
+
+    #[test]
+    fn search_and_quit() {
+        let mut board = board();
+        let mut app = App::new(&mut board, None);
+        app.handle_key(KeyCode::Char(' '));
+        assert_eq!(app.message, "The treasure is 4 blocs away.");
+        assert!(!app.is_done());
+        app.handle_key(KeyCode::Esc);
+        assert!(app.is_done());
+        assert_eq!(board.turns().len(), 1);
+    }
+
+    #[test]
+    fn bot_plays_its_turns() {
+        let mut board = Board::from_seed(BoardConfig::new(6, 6, 2).with_players(2), 1);
+        let mut bot = Bot::new(&board, 1);
+        let mut app = App::new(&mut board, Some(&mut bot));
+        app.handle_key(KeyCode::Char(' '));
+        assert_eq!(app.board.turns().len(), 2);
+        assert_eq!(app.board.current_player(), 0);
+    }
+
+    #[test]
+    fn draw_board_and_panel() {
+        let mut board = board();
+        let mut app = App::new(&mut board, None);
+        app.handle_key(KeyCode::Char(' '));
+        let screen = screen(&app);
+        assert!(screen.contains(Board::PLAYER_CHAR));
+        assert!(screen.contains("Treasures left: 1"));
+        assert!(screen.contains("1. P1 searched (1,1): 4 blocs"));
+    }
+
+    #[test]
+    fn last_search_stays_after_a_move() {
+        let config = BoardConfig::new(6, 6, 2).with_hints(HintMode::Compass);
+        let mut board = Board::from_seed(config, 1);
+        board.treasures[0].position.set((5, 5));
+        board.players[0].position.set((1, 1));
+        let mut app = App::new(&mut board, None);
+        app.handle_key(KeyCode::Char(' '));
+        app.handle_key(KeyCode::Up);
+        app.handle_key(KeyCode::Enter);
+        let screen = screen(&app);
+        assert!(screen.contains("Moved to (1,2)."));
+        assert!(screen.contains("Last search: (1,1): 4 blocs, to the NE"));
+        assert!(screen.contains("1. P1 searched (1,1): 4 blocs, to the NE"));
+    }
+}
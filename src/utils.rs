@@ -1,36 +1,58 @@
-use std::io::{self, Write};
-
-use rand::distributions::{Distribution, Standard};
+use rand::distributions::Distribution;
 use rand::Rng;
-use read_input::prelude::input;
-use read_input::InputBuild;
-use termcolor::{BufferWriter, Color, ColorChoice, ColorSpec, WriteColor};
+use serde::{Deserialize, Serialize};
+use std::cmp::max;
+use strum_macros::{Display, EnumString};
 
-use crate::board::Board;
+use crate::board::BoardConfig;
+use crate::command::{Command, Registry};
+use crate::input::Input;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Point {
-    pub(crate) x: u8,
-    pub(crate) y: u8,
+    pub x: u8,
+    pub y: u8,
 }
 
 impl Point {
-    #[allow(dead_code)]
-    pub(crate) fn as_tuple(&self) -> (u8, u8) {
+    pub fn as_tuple(&self) -> (u8, u8) {
         (self.x, self.y)
     }
     pub fn set(&mut self, p: (u8, u8)) {
         self.x = p.0;
         self.y = p.1;
     }
+    /// Distance in blocs between two points, measured with `metric`
+    pub fn distance_to(&self, other: &Point, metric: Metric) -> u16 {
+        let dx = abs(self.x as i32 - other.x as i32) as u16;
+        let dy = abs(self.y as i32 - other.y as i32) as u16;
+        match metric {
+            Metric::Chebyshev => max(dx, dy),
+            Metric::Manhattan => dx + dy,
+            Metric::Euclidean => ((dx as u32 * dx as u32 + dy as u32 * dy as u32) as f64)
+                .sqrt()
+                .round() as u16,
+        }
+    }
 }
 
-impl Distribution<Point> for Standard {
+/// How distances are measured, for the movement limit as well as the search hints
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, EnumString, Display, Default)]
+#[strum(serialize_all = "lowercase")]
+pub enum Metric {
+    /// Moving diagonally counts as one bloc: max(|dx|,|dy|)
+    #[default]
+    Chebyshev,
+    /// Only horizontal and vertical steps count: |dx|+|dy|
+    Manhattan,
+    /// Straight line distance, rounded to the nearest bloc
+    Euclidean,
+}
+
+/// Samples a random `Point` within the bounds of the board
+impl Distribution<Point> for BoardConfig {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Point {
-        let (rand_x, rand_y) = (
-            rng.gen_range(0..Board::BOARD_WIDTH),
-            rng.gen_range(0..Board::BOARD_HEIGHT),
-        );
+        let (rand_x, rand_y) = (rng.gen_range(0..self.width), rng.gen_range(0..self.height));
         Point {
             x: rand_x,
             y: rand_y,
@@ -38,26 +60,17 @@ impl Distribution<Point> for Standard {
     }
 }
 
+/// Colours a player can choose by their English name
+pub const KNOWN_COLORS: [&str; 8] = [
+    "Black", "Blue", "Green", "Red", "Cyan", "Magenta", "Yellow", "White",
+];
+
 /// Takes a string of a colour in English and checks if it's known
-#[allow(dead_code)]
 pub fn check_color(color: &str) -> bool {
-    let colors = [
-        "Black", "Blue", "Green", "Red", "Cyan", "Magenta", "Yellow", "White",
-    ];
-
-    if colors.contains(&color) {
-        return true;
-    } else {
-        println!("\"{}\" was not understood. The known colours are:", color);
-        for c in &colors {
-            println!("   * {}", c);
-        }
-        return false;
-    }
+    KNOWN_COLORS.contains(&color)
 }
 
 /// simple function to return the absolute value of an i32
-#[allow(dead_code)]
 pub fn abs(x: i32) -> i32 {
     if x >= 0 {
         x
@@ -66,53 +79,20 @@ pub fn abs(x: i32) -> i32 {
     }
 }
 
-#[allow(dead_code)]
-pub fn print_rules() -> io::Result<()> {
-    let buffer_writer = BufferWriter::stderr(ColorChoice::Always);
-    let mut buffer = buffer_writer.buffer();
-    const WHITE: Option<Color> = Some(Color::White);
-    const HL: Option<Color> = Some(Color::Green);
-
-    buffer.set_color(ColorSpec::new().set_fg(WHITE))?;
-    writeln!(&mut buffer, "{}", "Welcome to the Treasure Hunt!\n")?;
-    write!(&mut buffer, "{}", "You can ")?;
-    buffer.set_color(ColorSpec::new().set_fg(HL))?;
-    write!(&mut buffer, "{}", "Move")?;
-    buffer.set_color(ColorSpec::new().set_fg(WHITE))?;
-    write!(&mut buffer, "{}", " around the place to ")?;
-
-    buffer.set_color(ColorSpec::new().set_fg(HL))?;
-    write!(&mut buffer, "{}", "Search")?;
-    buffer.set_color(ColorSpec::new().set_fg(WHITE))?;
-    writeln!(&mut buffer, "{}", " for the Treasure! Good Luck...")?;
-
-    writeln!(&mut buffer, "\t[*] Search will take one action, it lets you search for the Treasure on your current coordinates.")?;
-    writeln!(&mut buffer, "\t[*] \"Move (x,y)\" or \"Move [x,y]\" to go to a coordinate.\n\t[*] You can only move within the board and you can only Move {} blocs away at most.",Board::MAX_DIST)?;
-    writeln!(&mut buffer, "You are represented by the character '{}' on the map, an '{}' signifies you have searched the area, and a '#' is a wall.\n",Board::PLAYER_CHAR, Board::SEARCHED_CHAR)?;
-
-    return buffer_writer.print(&buffer);
-}
-
 /// At the start of each turn the player is asked for an action that can be chosen from a menu
 /// This function enables us to print the menu and get the user's input
-#[allow(dead_code)]
-pub fn ask_for_action() -> String {
-    input()
-        .repeat_msg(
-            "Choose one of the following:\n1. Move          3. Help\n2. Search        4. Quit\n",
-        )
-        .inside([
-            "1".to_string(),
-            "2".to_string(),
-            "3".to_string(),
-            "4".to_string(),
-            "Move".to_string(),
-            "Search".to_string(),
-            "Help".to_string(),
-            "Quit".to_string(),
-        ])
-        .err("You can only input a number from 1 to 4 included, or the command name displayed!")
-        .get()
+///
+/// Returns None once the input is exhausted.
+pub fn ask_for_action(input: &mut Input, registry: &Registry) -> Option<Command> {
+    const MENU: &str = "Choose one of the following:\n\
+        1. Move (x,y)    3. Help          5. Save <path>   7. Scores        9. Redo\n\
+        2. Search        4. Quit          6. Load <path>   8. Undo\n";
+    loop {
+        match registry.parse(&input.read_line(MENU)?) {
+            Ok(command) => return Some(command),
+            Err(err) => println!("{}", err),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -134,4 +114,27 @@ mod test {
     fn utils_color_tests(input: &str, expected: bool) {
         assert_eq!(check_color(input), expected);
     }
+
+    #[rstest(
+        metric,
+        expected,
+        case(Metric::Chebyshev, 4),
+        case(Metric::Manhattan, 7),
+        case(Metric::Euclidean, 5),
+        ::trace
+    )]
+    fn utils_metric_tests(metric: Metric, expected: u16) {
+        let a = Point { x: 1, y: 6 };
+        let b = Point { x: 4, y: 2 };
+        assert_eq!(a.distance_to(&b, metric), expected);
+        assert_eq!(b.distance_to(&a, metric), expected);
+    }
+
+    #[test]
+    fn utils_metric_large_board() {
+        let a = Point { x: 0, y: 0 };
+        let b = Point { x: 255, y: 255 };
+        assert_eq!(a.distance_to(&b, Metric::Manhattan), 510);
+        assert_eq!(a.distance_to(&b, Metric::Euclidean), 361);
+    }
 }
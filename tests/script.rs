@@ -0,0 +1,62 @@
+//! End-to-end games played by the `lab01` binary from a script
+
+use std::fs;
+use std::process::Command;
+
+/// Runs `lab01 --script` on `script` with a fixed seed on a 5x5 board and returns what it printed
+fn run_script(name: &str, script: &str) -> String {
+    run_script_with(name, &["--width", "5", "--height", "5"], script)
+}
+
+/// Runs `lab01 --script` on `script` with a fixed seed and the options `args`
+fn run_script_with(name: &str, args: &[&str], script: &str) -> String {
+    let path = std::env::temp_dir().join(format!("lab01_{}_{}.txt", name, std::process::id()));
+    fs::write(&path, script).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_lab01"))
+        .args(["--seed", "3"])
+        .args(args)
+        .arg("--script")
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn script_until_eof() {
+    // the game ends with the script even though the player was asked for coordinates
+    let output = run_script("eof", "Green\nsearch\nmove (9,9)\nmove\n");
+    assert!(output.contains("The treasure is"));
+    assert!(output.contains("Max width is 4"));
+    assert!(output.contains("Turns taken:     1"));
+}
+
+#[test]
+fn script_is_deterministic() {
+    let script = "Green\nsearch\nmove (2,2)\nsearch\nquit\n";
+    assert_eq!(run_script("first", script), run_script("second", script));
+}
+
+#[test]
+fn script_without_board_options() {
+    // scripts are not asked for a difficulty, they play the default board
+    let output = run_script_with("default", &[], "Green\nsearch\nmove (2,2)\nsearch\nquit\n");
+    assert!(!output.contains("is not a difficulty"));
+    assert!(output.contains("14 #"));
+    assert!(output.contains("Searches:        2"));
+}
+
+#[test]
+fn script_with_difficulty() {
+    // the difficulty is applied first wherever it is given, the other options override it
+    let script = "Green\nsearch\nquit\n";
+    let after = run_script_with("after", &["--width", "12", "--difficulty", "hard"], script);
+    let before = run_script_with("before", &["--difficulty", "hard", "--width", "12"], script);
+    assert_eq!(after, before);
+    assert!(after.contains("19 #"));
+    // a value of another option is not a difficulty
+    let output = run_script_with("value", &["--aliases", "--difficulty"], script);
+    assert!(output.contains("14 #"));
+}